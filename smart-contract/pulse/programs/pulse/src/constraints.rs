@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use crate::error::TixError;
+use crate::state::{Event, EventSchedule, SaleWindow, TicketTier};
+use crate::math::BPS_DENOMINATOR;
+
+// Validation helper functions
+
+pub fn validate_event_timing(
+    event_start: i64,
+    event_end: i64,
+    sale_start: i64,
+    sale_end: i64,
+) -> Result<()> {
+    require!(event_start > 0, TixError::InvalidEventTiming);
+    require!(event_end > event_start, TixError::InvalidEventTiming);
+    require!(sale_start > 0, TixError::InvalidEventTiming);
+    require!(sale_end > sale_start, TixError::InvalidEventTiming);
+    require!(sale_end < event_start, TixError::InvalidEventTiming);
+    Ok(())
+}
+
+pub fn validate_event_schedule(schedule: &EventSchedule) -> Result<()> {
+    validate_event_timing(
+        schedule.event_start_time,
+        schedule.event_end_time,
+        schedule.sale_start_time,
+        schedule.sale_end_time,
+    )
+}
+
+/// A tier override may only narrow the event's own sale window
+pub fn validate_tier_window(event: &Event, window: &SaleWindow) -> Result<()> {
+    require!(window.end_time > window.start_time, TixError::InvalidEventTiming);
+    require!(window.start_time >= event.sale_start_time, TixError::InvalidEventTiming);
+    require!(window.end_time <= event.sale_end_time, TixError::InvalidEventTiming);
+    Ok(())
+}
+
+/// Reject purchases outside the event's sale window or the tier's override
+pub fn check_sale_window(event: &Event, tier: &TicketTier, now: i64) -> Result<()> {
+    require!(now >= event.sale_start_time, TixError::SaleNotStarted);
+    require!(now <= event.sale_end_time, TixError::SaleEnded);
+
+    if tier.has_sale_window() {
+        require!(now >= tier.sale_start_time, TixError::TierSaleNotStarted);
+        require!(now <= tier.sale_end_time, TixError::TierSaleEnded);
+    }
+
+    Ok(())
+}
+
+/// Resale price may not exceed face value plus `price_cap_bps`
+pub fn validate_price_cap(price: u64, original_price: u64, price_cap_bps: u16) -> Result<()> {
+    let max_price = (original_price as u128)
+        .checked_mul(BPS_DENOMINATOR as u128 + price_cap_bps as u128)
+        .ok_or(TixError::MathOverflow)?
+        / BPS_DENOMINATOR as u128;
+
+    require!(price as u128 <= max_price, TixError::PriceCapExceeded);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_cap_allows_markup_up_to_cap() {
+        assert!(validate_price_cap(1_100, 1_000, 1_000).is_ok());
+        assert!(validate_price_cap(1_101, 1_000, 1_000).is_err());
+        assert!(validate_price_cap(1_000, 1_000, 0).is_ok());
+        assert!(validate_price_cap(1_001, 1_000, 0).is_err());
+    }
+
+    #[test]
+    fn price_cap_rounds_down_and_handles_large_prices() {
+        assert!(validate_price_cap(1_008, 999, 100).is_ok());
+        assert!(validate_price_cap(1_009, 999, 100).is_err());
+        assert!(validate_price_cap(u64::MAX, u64::MAX, 0).is_ok());
+    }
+}
@@ -32,4 +32,136 @@ pub enum TixError {
     MathOverflow,
     #[msg("Math operation underflow")]
     MathUnderflow,
+    #[msg("Signer is not the agent owner or a registered delegate")]
+    InvalidDelegate,
+    #[msg("Delegate key has expired")]
+    DelegateExpired,
+    #[msg("Delegate is not allowed to perform this instruction")]
+    DelegateScopeNotAllowed,
+    #[msg("Delegate spend cap exceeded")]
+    DelegateSpendCapExceeded,
+    #[msg("Ticket mint requires a purchase receipt")]
+    PurchaseReceiptRequired,
+    #[msg("Purchase receipt does not match this event or buyer")]
+    PurchaseReceiptMismatch,
+    #[msg("Maximum tickets per agent for this event exceeded")]
+    MaxTicketsPerAgentExceeded,
+    #[msg("Maximum tickets per user for this event exceeded")]
+    MaxTicketsPerUserExceeded,
+    #[msg("Event is already cancelled")]
+    EventAlreadyCancelled,
+    #[msg("Event is already active")]
+    EventAlreadyActive,
+    #[msg("Sales have already started - cannot modify")]
+    SaleAlreadyStarted,
+    #[msg("Event must be paused or cancelled first")]
+    EventStillActive,
+    #[msg("Refunds are still pending for this event")]
+    RefundsPending,
+    #[msg("Event still has open ticket tiers")]
+    TiersStillOpen,
+    #[msg("Tier is already cancelled")]
+    TierAlreadyCancelled,
+    #[msg("Ticket is not refundable")]
+    NotRefundable,
+    #[msg("Ticket has already been refunded")]
+    TicketAlreadyRefunded,
+    #[msg("Refund recipient does not match the ticket's funding source")]
+    InvalidRefundRecipient,
+    #[msg("Insufficient vault balance")]
+    InsufficientVaultBalance,
+    #[msg("Token account does not hold this ticket")]
+    TicketNotHeld,
+    #[msg("Settlement time must not be in the past")]
+    InvalidSettlementTime,
+    #[msg("Proceeds are still locked until settlement")]
+    SettlementPending,
+    #[msg("Vault balance does not reconcile with event revenue")]
+    VaultOutOfBalance,
+    #[msg("Invalid event timing")]
+    InvalidEventTiming,
+    #[msg("Ticket sales have not started yet")]
+    SaleNotStarted,
+    #[msg("Ticket sales have ended")]
+    SaleEnded,
+    #[msg("Sales for this tier have not started yet")]
+    TierSaleNotStarted,
+    #[msg("Sales for this tier have ended")]
+    TierSaleEnded,
+    #[msg("Tier has sales - repricing must be explicitly allowed")]
+    TierPriceLocked,
+    #[msg("Tier is already in the requested state")]
+    TierStateUnchanged,
+    #[msg("Tier with sales can only close once cancelled or the event has ended")]
+    TierNotFinished,
+    #[msg("Invalid price schedule")]
+    InvalidPriceSchedule,
+    #[msg("Payment mint does not match the tier's currency")]
+    WrongPaymentMint,
+    #[msg("Ticket is not active")]
+    TicketNotActive,
+    #[msg("Seat info is too long")]
+    SeatInfoTooLong,
+    #[msg("Event has been cancelled")]
+    EventCancelled,
+    #[msg("Check-in is only open during the event")]
+    EventNotOngoing,
+    #[msg("Ticket is already checked in")]
+    AlreadyCheckedIn,
+    #[msg("Ticket is not checked in")]
+    NotCheckedIn,
+    #[msg("Re-entry is not allowed for this ticket")]
+    ReentryNotAllowed,
+    #[msg("Holder signature is missing or does not match this check-in")]
+    InvalidHolderSignature,
+    #[msg("Check-in signature has expired or is valid for too long")]
+    CheckinSignatureExpired,
+    #[msg("Check-in nonce must be higher than the last one used for this ticket")]
+    CheckinNonceReused,
+    #[msg("Listing has expired")]
+    ListingExpired,
+    #[msg("Listing has not expired yet")]
+    ListingNotExpired,
+    #[msg("Listing duration is outside the allowed range")]
+    InvalidListingDuration,
+    #[msg("Cannot list tickets after event start")]
+    CannotListAfterEventStart,
+    #[msg("Price exceeds the buyer's maximum")]
+    PriceAboveMax,
+    #[msg("Price exceeds the event's resale price cap")]
+    PriceCapExceeded,
+    #[msg("Cannot make offers after event start")]
+    CannotOfferAfterEventStart,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Offer has not expired yet")]
+    OfferNotExpired,
+    #[msg("Price is above the agent's auto-purchase threshold")]
+    PriceAboveThreshold,
+    #[msg("Tier still has seats for sale")]
+    TierNotSoldOut,
+    #[msg("Face-value resales go to the waitlist first")]
+    ReservedForWaitlist,
+    #[msg("Waitlist entry is no longer active")]
+    WaitlistEntryInactive,
+    #[msg("No claim window is open for the waitlist head")]
+    ClaimWindowClosed,
+    #[msg("Waitlist head cannot be skipped yet")]
+    WaitlistHeadNotSkippable,
+    #[msg("Token metadata program does not match Metaplex")]
+    InvalidMetadataProgram,
+    #[msg("Proceeds were withdrawn; fund them back before cancelling")]
+    ProceedsWithdrawn,
+    #[msg("Ticket is listed for resale")]
+    TicketListed,
+    #[msg("Waitlist still has entries queued")]
+    WaitlistNotEmpty,
+    #[msg("Waitlist seats must be claimed by the head within its claim window")]
+    WaitlistClaimRequired,
+    #[msg("Event still has open token vaults")]
+    TokenVaultsStillOpen,
+    #[msg("Token vault still holds proceeds")]
+    TokenVaultNotEmpty,
+    #[msg("Organizer fees are still held in the vault")]
+    OrganizerFeesHeld,
 }
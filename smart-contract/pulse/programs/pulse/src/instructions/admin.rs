@@ -0,0 +1,256 @@
+use anchor_lang::prelude::*;
+
+use crate::state::GlobalConfig;
+use crate::error::TixError;
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+pub fn initialize_config(
+    ctx: Context<InitializeConfig>,
+    protocol_fee_bps: u16,
+    default_price_cap_bps: u16,
+    min_listing_duration: i64,
+    max_listing_duration: i64,
+    allow_agent_coordination: bool,
+    require_verification: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    require!(protocol_fee_bps <= 10000, TixError::InvalidFeeBps);
+    require!(default_price_cap_bps <= 10000, TixError::InvalidFeeBps);
+    require!(min_listing_duration > 0, TixError::InvalidInput);
+    require!(max_listing_duration > min_listing_duration, TixError::InvalidInput);
+
+    config.admin = ctx.accounts.admin.key();
+    config.pending_admin = Pubkey::default();
+    config.protocol_fee_bps = protocol_fee_bps;
+    config.default_price_cap_bps = default_price_cap_bps;
+    config.min_listing_duration = min_listing_duration;
+    config.max_listing_duration = max_listing_duration;
+    config.allow_agent_coordination = allow_agent_coordination;
+    config.require_verification = require_verification;
+    config.treasury = ctx.accounts.admin.key(); // Default to admin
+    config.default_settlement_delay = 0;
+    config.default_early_release_bps = 0;
+    config.default_max_tickets_per_wallet = 0;
+    config.bump = ctx.bumps.config;
+
+    emit!(ConfigUpdated::from_config(config, Clock::get()?.unix_timestamp));
+
+    msg!("Global config initialized");
+    Ok(())
+}
+
+/// Change fee, listing and event-default parameters. Unset fields are left as is.
+pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    if let Some(fee) = params.protocol_fee_bps {
+        require!(fee <= 10000, TixError::InvalidFeeBps);
+        config.protocol_fee_bps = fee;
+    }
+
+    if let Some(cap) = params.default_price_cap_bps {
+        require!(cap <= 10000, TixError::InvalidFeeBps);
+        config.default_price_cap_bps = cap;
+    }
+
+    if let Some(min_duration) = params.min_listing_duration {
+        require!(min_duration > 0, TixError::InvalidInput);
+        config.min_listing_duration = min_duration;
+    }
+
+    if let Some(max_duration) = params.max_listing_duration {
+        config.max_listing_duration = max_duration;
+    }
+
+    require!(
+        config.max_listing_duration > config.min_listing_duration,
+        TixError::InvalidInput
+    );
+
+    if let Some(allowed) = params.allow_agent_coordination {
+        config.allow_agent_coordination = allowed;
+    }
+
+    if let Some(required) = params.require_verification {
+        config.require_verification = required;
+    }
+
+    if let Some(new_treasury) = params.treasury {
+        require!(new_treasury != Pubkey::default(), TixError::InvalidInput);
+        config.treasury = new_treasury;
+    }
+
+    if let Some(delay) = params.default_settlement_delay {
+        require!(delay >= 0, TixError::InvalidSettlementTime);
+        config.default_settlement_delay = delay;
+    }
+
+    if let Some(release_bps) = params.default_early_release_bps {
+        require!(release_bps <= 10000, TixError::InvalidFeeBps);
+        config.default_early_release_bps = release_bps;
+    }
+
+    if let Some(max_tickets) = params.default_max_tickets_per_wallet {
+        config.default_max_tickets_per_wallet = max_tickets;
+    }
+
+    emit!(ConfigUpdated::from_config(config, Clock::get()?.unix_timestamp));
+
+    msg!("Config updated");
+    Ok(())
+}
+
+/// First half of an admin handover. Proposing the default key clears a pending proposal.
+pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    require!(new_admin != config.admin, TixError::InvalidInput);
+    config.pending_admin = new_admin;
+
+    emit!(AdminTransferProposed {
+        admin: config.admin,
+        pending_admin: new_admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Admin transfer proposed to {}", new_admin);
+    Ok(())
+}
+
+/// Second half of an admin handover, signed by the proposed admin
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+
+    config.admin = ctx.accounts.pending_admin.key();
+    config.pending_admin = Pubkey::default();
+
+    emit!(AdminTransferAccepted {
+        previous_admin,
+        admin: config.admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Admin transferred from {} to {}", previous_admin, config.admin);
+    Ok(())
+}
+
+// =====================================
+// PARAMS & EVENTS
+// =====================================
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ConfigParams {
+    pub protocol_fee_bps: Option<u16>,
+    pub default_price_cap_bps: Option<u16>,
+    pub min_listing_duration: Option<i64>,
+    pub max_listing_duration: Option<i64>,
+    pub allow_agent_coordination: Option<bool>,
+    pub require_verification: Option<bool>,
+    pub treasury: Option<Pubkey>,
+    pub default_settlement_delay: Option<i64>,
+    pub default_early_release_bps: Option<u16>,
+    pub default_max_tickets_per_wallet: Option<u32>,
+}
+
+/// Full snapshot of the config after any change
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub default_price_cap_bps: u16,
+    pub min_listing_duration: i64,
+    pub max_listing_duration: i64,
+    pub allow_agent_coordination: bool,
+    pub require_verification: bool,
+    pub default_settlement_delay: i64,
+    pub default_early_release_bps: u16,
+    pub default_max_tickets_per_wallet: u32,
+    pub timestamp: i64,
+}
+
+impl ConfigUpdated {
+    fn from_config(config: &GlobalConfig, timestamp: i64) -> Self {
+        Self {
+            admin: config.admin,
+            treasury: config.treasury,
+            protocol_fee_bps: config.protocol_fee_bps,
+            default_price_cap_bps: config.default_price_cap_bps,
+            min_listing_duration: config.min_listing_duration,
+            max_listing_duration: config.max_listing_duration,
+            allow_agent_coordination: config.allow_agent_coordination,
+            require_verification: config.require_verification,
+            default_settlement_delay: config.default_settlement_delay,
+            default_early_release_bps: config.default_early_release_bps,
+            default_max_tickets_per_wallet: config.default_max_tickets_per_wallet,
+            timestamp,
+        }
+    }
+}
+
+#[event]
+pub struct AdminTransferProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferAccepted {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        seeds = [b"config"],
+        bump,
+        payer = admin,
+        space = GlobalConfig::SPACE
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ TixError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin != Pubkey::default() @ TixError::Unauthorized,
+        constraint = config.pending_admin == pending_admin.key() @ TixError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub pending_admin: Signer<'info>,
+}
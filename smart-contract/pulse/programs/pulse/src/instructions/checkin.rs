@@ -0,0 +1,406 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+#[allow(deprecated)]
+use anchor_lang::solana_program::sysvar::instructions::{self as instructions_sysvar, get_instruction_relative};
+
+use crate::state::{Event, GateOperator, Ticket, TicketStatus};
+use crate::error::TixError;
+
+/// Longest a holder's check-in signature may stay valid, in seconds
+pub const MAX_CHECKIN_SIGNATURE_TTL: i64 = 300;
+
+/// Native Ed25519 signature verification program
+pub const ED25519_PROGRAM_ID: Pubkey = pubkey!("Ed25519SigVerify111111111111111111111111111");
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// Organizer authorizes a key to scan tickets at the event's gates
+pub fn add_gate_operator(ctx: Context<AddGateOperator>, operator: Pubkey) -> Result<()> {
+    let gate_operator = &mut ctx.accounts.gate_operator;
+
+    gate_operator.event = ctx.accounts.event.key();
+    gate_operator.operator = operator;
+    gate_operator.added_at = Clock::get()?.unix_timestamp;
+    gate_operator.bump = ctx.bumps.gate_operator;
+
+    msg!("Gate operator {} added for event {}", operator, ctx.accounts.event.event_id);
+    Ok(())
+}
+
+/// Organizer revokes a gate operator; the account is closed
+pub fn remove_gate_operator(ctx: Context<RemoveGateOperator>) -> Result<()> {
+    msg!(
+        "Gate operator {} removed for event {}",
+        ctx.accounts.gate_operator.operator,
+        ctx.accounts.event.event_id
+    );
+    Ok(())
+}
+
+/// Gate operator scans a ticket in with a fresh signature from the holder over
+/// `checkin_message(mint, event, nonce, expires_at)`, verified by an Ed25519
+/// program instruction placed immediately before this one. Nonces must increase,
+/// so an older signed payload cannot be replayed. The first scan consumes the
+/// ticket; later scans are re-entries and follow the event's re-entry policy.
+pub fn check_in_with_signature(ctx: Context<SignedGateScan>, nonce: u64, expires_at: i64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let holder = ctx.accounts.holder.key();
+
+    require!(now <= expires_at, TixError::CheckinSignatureExpired);
+    require!(
+        expires_at <= now.saturating_add(MAX_CHECKIN_SIGNATURE_TTL),
+        TixError::CheckinSignatureExpired
+    );
+    require!(nonce > ctx.accounts.ticket.last_checkin_nonce, TixError::CheckinNonceReused);
+
+    let message = checkin_message(&ctx.accounts.ticket.mint, &ctx.accounts.event.key(), nonce, expires_at);
+    verify_ed25519_signature(&ctx.accounts.instructions_sysvar, &holder, &message)?;
+
+    record_entry(&ctx.accounts.event, &mut ctx.accounts.ticket, now)?;
+    ctx.accounts.ticket.last_checkin_nonce = nonce;
+
+    let ticket = &ctx.accounts.ticket;
+    emit!(TicketCheckedIn {
+        event: ctx.accounts.event.key(),
+        tier: ticket.tier,
+        ticket: ticket.mint,
+        holder,
+        operator: ctx.accounts.operator.key(),
+        entry_count: ticket.entry_count,
+        is_reentry: ticket.entry_count > 1,
+        timestamp: now,
+    });
+
+    msg!("Ticket {} checked in with holder signature (entry {})", ticket.mint, ticket.entry_count);
+    Ok(())
+}
+
+/// Gate operator scans a ticket out, so it can come back in where re-entry is allowed
+pub fn check_out_ticket(ctx: Context<GateScan>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let ticket = &mut ctx.accounts.ticket;
+
+    require!(ticket.status == TicketStatus::Consumed, TixError::NotCheckedIn);
+    require!(ticket.is_inside, TixError::NotCheckedIn);
+
+    ticket.is_inside = false;
+    ticket.last_exit_at = now;
+
+    emit!(TicketCheckedOut {
+        event: ctx.accounts.event.key(),
+        tier: ticket.tier,
+        ticket: ticket.mint,
+        holder: ctx.accounts.holder.key(),
+        operator: ctx.accounts.operator.key(),
+        entry_count: ticket.entry_count,
+        timestamp: now,
+    });
+
+    msg!("Ticket {} checked out", ticket.mint);
+    Ok(())
+}
+
+// =====================================
+// HELPERS
+// =====================================
+
+/// Validate and record one entry through the gate
+pub fn record_entry(event: &Event, ticket: &mut Ticket, now: i64) -> Result<()> {
+    require!(!event.is_cancelled, TixError::EventCancelled);
+    require!(event.is_ongoing(now), TixError::EventNotOngoing);
+
+    match ticket.status {
+        TicketStatus::Active => {
+            ticket.status = TicketStatus::Consumed;
+            ticket.validated_at = Some(now);
+        }
+        TicketStatus::Consumed => {
+            require!(!ticket.is_inside, TixError::AlreadyCheckedIn);
+            require!(
+                event.reentry_policy.allows_entry(ticket.entry_count),
+                TixError::ReentryNotAllowed
+            );
+        }
+        TicketStatus::Refunded => return err!(TixError::TicketAlreadyRefunded),
+    }
+
+    ticket.entry_count = ticket.entry_count.checked_add(1).ok_or(TixError::MathOverflow)?;
+    ticket.is_inside = true;
+    ticket.last_entry_at = now;
+    Ok(())
+}
+
+/// Bytes the holder signs for a check-in: mint, event, nonce and expiry (little-endian)
+pub fn checkin_message(mint: &Pubkey, event: &Pubkey, nonce: u64, expires_at: i64) -> [u8; 80] {
+    let mut message = [0u8; 80];
+    message[..32].copy_from_slice(mint.as_ref());
+    message[32..64].copy_from_slice(event.as_ref());
+    message[64..72].copy_from_slice(&nonce.to_le_bytes());
+    message[72..].copy_from_slice(&expires_at.to_le_bytes());
+    message
+}
+
+/// Require that the previous instruction is an Ed25519 program check of
+/// exactly one signature by `signer` over `message`. The precompile has
+/// already failed the transaction if the signature itself is invalid.
+fn verify_ed25519_signature(instructions_sysvar: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let ix = get_instruction_relative(-1, instructions_sysvar)
+        .map_err(|_| error!(TixError::InvalidHolderSignature))?;
+    require!(ix.program_id == ED25519_PROGRAM_ID, TixError::InvalidHolderSignature);
+    require!(ix.accounts.is_empty(), TixError::InvalidHolderSignature);
+
+    let (signed_key, signed_message) =
+        parse_ed25519_instruction(&ix.data).ok_or(TixError::InvalidHolderSignature)?;
+    require!(signed_key == signer.as_ref(), TixError::InvalidHolderSignature);
+    require!(signed_message == message, TixError::InvalidHolderSignature);
+    Ok(())
+}
+
+/// Public key and message of a single-signature Ed25519 instruction whose
+/// offsets all point into its own data
+fn parse_ed25519_instruction(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    const HEADER_LEN: usize = 2;
+    const OFFSETS_LEN: usize = 14;
+    const PUBKEY_LEN: usize = 32;
+    const SIGNATURE_LEN: usize = 64;
+    const THIS_INSTRUCTION: u16 = u16::MAX;
+
+    if data.len() < HEADER_LEN + OFFSETS_LEN || data[0] != 1 {
+        return None;
+    }
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[HEADER_LEN + at], data[HEADER_LEN + at + 1]]);
+    let signature_offset = read_u16(0) as usize;
+    let signature_ix = read_u16(2);
+    let pubkey_offset = read_u16(4) as usize;
+    let pubkey_ix = read_u16(6);
+    let message_offset = read_u16(8) as usize;
+    let message_len = read_u16(10) as usize;
+    let message_ix = read_u16(12);
+
+    if signature_ix != THIS_INSTRUCTION || pubkey_ix != THIS_INSTRUCTION || message_ix != THIS_INSTRUCTION {
+        return None;
+    }
+    data.get(signature_offset..signature_offset.checked_add(SIGNATURE_LEN)?)?;
+
+    let pubkey = data.get(pubkey_offset..pubkey_offset.checked_add(PUBKEY_LEN)?)?;
+    let message = data.get(message_offset..message_offset.checked_add(message_len)?)?;
+    Some((pubkey, message))
+}
+
+// =====================================
+// EVENTS
+// =====================================
+
+/// Attendance record for analytics, one per entry
+#[event]
+pub struct TicketCheckedIn {
+    pub event: Pubkey,
+    pub tier: Pubkey,
+    pub ticket: Pubkey,
+    pub holder: Pubkey,
+    pub operator: Pubkey,
+    pub entry_count: u16,
+    pub is_reentry: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketCheckedOut {
+    pub event: Pubkey,
+    pub tier: Pubkey,
+    pub ticket: Pubkey,
+    pub holder: Pubkey,
+    pub operator: Pubkey,
+    pub entry_count: u16,
+    pub timestamp: i64,
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+#[instruction(operator: Pubkey)]
+pub struct AddGateOperator<'info> {
+    #[account(
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        init,
+        seeds = [b"gate_operator", event.key().as_ref(), operator.as_ref()],
+        bump,
+        payer = organizer,
+        space = GateOperator::SPACE
+    )]
+    pub gate_operator: Account<'info, GateOperator>,
+
+    #[account(
+        mut,
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveGateOperator<'info> {
+    #[account(
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"gate_operator", event.key().as_ref(), gate_operator.operator.as_ref()],
+        bump = gate_operator.bump,
+        close = organizer
+    )]
+    pub gate_operator: Account<'info, GateOperator>,
+
+    #[account(
+        mut,
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GateScan<'info> {
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket.mint.as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.event == event.key() @ TixError::InvalidInput
+    )]
+    pub ticket: Account<'info, Ticket>,
+
+    /// Holder's token account for the ticket NFT
+    #[account(
+        constraint = ticket.is_held_by(&holder_token_account, &holder.key()) @ TixError::TicketNotHeld
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+
+    /// Listed tickets sit in the listing's vault and cannot be scanned
+    #[account(
+        seeds = [b"listing", ticket.mint.as_ref()],
+        bump,
+        constraint = listing.data_is_empty() @ TixError::TicketListed
+    )]
+    /// CHECK: Listing PDA for the ticket, required not to exist
+    pub listing: UncheckedAccount<'info>,
+
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [b"gate_operator", event.key().as_ref(), operator.key().as_ref()],
+        bump = gate_operator.bump
+    )]
+    pub gate_operator: Account<'info, GateOperator>,
+
+    pub operator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SignedGateScan<'info> {
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket.mint.as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.event == event.key() @ TixError::InvalidInput
+    )]
+    pub ticket: Account<'info, Ticket>,
+
+    /// Holder's token account for the ticket NFT
+    #[account(
+        constraint = ticket.is_held_by(&holder_token_account, &holder.key()) @ TixError::TicketNotHeld
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+
+    /// Listed tickets sit in the listing's vault and cannot be scanned
+    #[account(
+        seeds = [b"listing", ticket.mint.as_ref()],
+        bump,
+        constraint = listing.data_is_empty() @ TixError::TicketListed
+    )]
+    /// CHECK: Listing PDA for the ticket, required not to exist
+    pub listing: UncheckedAccount<'info>,
+
+    /// CHECK: Proven by the Ed25519 signature the handler verifies
+    pub holder: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"gate_operator", event.key().as_ref(), operator.key().as_ref()],
+        bump = gate_operator.bump
+    )]
+    pub gate_operator: Account<'info, GateOperator>,
+
+    pub operator: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, checked by address
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Layout produced by the Ed25519 program's own instruction builder
+    fn ed25519_data(pubkey: &[u8; 32], message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let pubkey_offset = 16u16;
+        let signature_offset = pubkey_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1u8, 0];
+        for value in [
+            signature_offset,
+            instruction_index,
+            pubkey_offset,
+            instruction_index,
+            message_offset,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(pubkey);
+        data.extend_from_slice(&[7u8; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn parses_self_contained_signature() {
+        let message = checkin_message(&Pubkey::new_unique(), &Pubkey::new_unique(), 42, 1_000);
+        let data = ed25519_data(&[9u8; 32], &message, u16::MAX);
+
+        let (pubkey, signed) = parse_ed25519_instruction(&data).unwrap();
+        assert_eq!(pubkey, &[9u8; 32]);
+        assert_eq!(signed, &message);
+    }
+
+    #[test]
+    fn rejects_offsets_into_other_instructions() {
+        let data = ed25519_data(&[9u8; 32], b"check-in", 0);
+        assert!(parse_ed25519_instruction(&data).is_none());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let data = ed25519_data(&[9u8; 32], b"check-in", u16::MAX);
+        assert!(parse_ed25519_instruction(&data[..data.len() - 1]).is_none());
+        assert!(parse_ed25519_instruction(&data[..10]).is_none());
+    }
+}
@@ -0,0 +1,331 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AgentDelegate, AIAgent};
+use crate::error::TixError;
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// Register an executor key (e.g. the scheduler) that may act for the agent
+pub fn add_agent_delegate(
+    ctx: Context<AddAgentDelegate>,
+    delegate_key: Pubkey,
+    scopes: u8,
+    spend_cap: u64,
+    expires_at: i64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    validate_delegate_params(scopes, spend_cap, expires_at, clock.unix_timestamp)?;
+    require!(delegate_key != ctx.accounts.owner.key(), TixError::InvalidDelegate);
+
+    let entry = &mut ctx.accounts.delegate;
+    entry.agent = ctx.accounts.agent.key();
+    entry.delegate = delegate_key;
+    entry.scopes = scopes;
+    entry.spend_cap = spend_cap;
+    entry.spent = 0;
+    entry.expires_at = expires_at;
+    entry.created_at = clock.unix_timestamp;
+    entry.bump = ctx.bumps.delegate;
+
+    msg!("Delegate {} added to agent {}", delegate_key, ctx.accounts.agent.agent_id);
+    Ok(())
+}
+
+/// Revoke a delegate key and reclaim its rent
+pub fn revoke_agent_delegate(ctx: Context<RevokeAgentDelegate>) -> Result<()> {
+    msg!(
+        "Delegate {} revoked from agent {}",
+        ctx.accounts.delegate.delegate,
+        ctx.accounts.agent.agent_id
+    );
+    Ok(())
+}
+
+/// Move a delegate's scope, cap and expiry over to a new key
+pub fn rotate_agent_delegate(ctx: Context<RotateAgentDelegate>, new_delegate_key: Pubkey) -> Result<()> {
+    let clock = Clock::get()?;
+    let old = &ctx.accounts.delegate;
+
+    require!(old.is_live(clock.unix_timestamp), TixError::DelegateExpired);
+    require!(new_delegate_key != ctx.accounts.owner.key(), TixError::InvalidDelegate);
+
+    let entry = &mut ctx.accounts.new_delegate;
+    entry.agent = old.agent;
+    entry.delegate = new_delegate_key;
+    entry.scopes = old.scopes;
+    entry.spend_cap = old.spend_cap;
+    entry.spent = old.spent;
+    entry.expires_at = old.expires_at;
+    entry.created_at = clock.unix_timestamp;
+    entry.bump = ctx.bumps.new_delegate;
+
+    msg!("Delegate {} rotated to {}", old.delegate, new_delegate_key);
+    Ok(())
+}
+
+// =====================================
+// HELPERS
+// =====================================
+
+fn validate_delegate_params(scopes: u8, spend_cap: u64, expires_at: i64, now: i64) -> Result<()> {
+    require!(scopes != 0, TixError::InvalidInput);
+    require!(scopes & !AgentDelegate::ALL_SCOPES == 0, TixError::InvalidInput);
+    require!(spend_cap > 0, TixError::InvalidBudget);
+    require!(expires_at > now, TixError::DelegateExpired);
+    Ok(())
+}
+
+/// A live, in-scope entry registered to `authority` for `agent`
+fn check_delegate(delegate: &AgentDelegate, agent: &Pubkey, authority: &Pubkey, scope: u8, now: i64) -> Result<()> {
+    require!(delegate.agent == *agent, TixError::InvalidDelegate);
+    require!(delegate.delegate == *authority, TixError::InvalidDelegate);
+    require!(delegate.is_live(now), TixError::DelegateExpired);
+    require!(delegate.has_scope(scope), TixError::DelegateScopeNotAllowed);
+    Ok(())
+}
+
+/// Record `amount` against the delegate's spend cap
+fn charge_delegate(delegate: &mut AgentDelegate, amount: u64) -> Result<()> {
+    let spent = delegate.spent.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    require!(spent <= delegate.spend_cap, TixError::DelegateSpendCapExceeded);
+    delegate.spent = spent;
+    Ok(())
+}
+
+/// Take `amount` back off the delegate's spend cap
+fn refund_delegate(delegate: &mut AgentDelegate, amount: u64) {
+    delegate.spent = delegate.spent.saturating_sub(amount);
+}
+
+/// Check that `authority` may act for `agent` within `scope`. The owner
+/// always can; any other signer needs a live, in-scope delegate.
+/// Returns the delegate entry when one was used.
+pub fn authorize_agent_action<'a, 'info>(
+    agent: &Account<AIAgent>,
+    delegate: Option<&'a mut Account<'info, AgentDelegate>>,
+    authority: &Pubkey,
+    scope: u8,
+    now: i64,
+) -> Result<Option<&'a mut Account<'info, AgentDelegate>>> {
+    if *authority == agent.owner {
+        return Ok(None);
+    }
+
+    let delegate = delegate.ok_or(TixError::InvalidDelegate)?;
+    check_delegate(delegate, &agent.key(), authority, scope, now)?;
+
+    Ok(Some(delegate))
+}
+
+/// `authorize_agent_action` plus the delegate's lamport spend cap.
+/// Delegate spend is recorded here.
+pub fn authorize_agent_spend(
+    agent: &Account<AIAgent>,
+    delegate: Option<&mut Account<AgentDelegate>>,
+    authority: &Pubkey,
+    scope: u8,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    let Some(delegate) = authorize_agent_action(agent, delegate, authority, scope, now)? else {
+        return Ok(());
+    };
+
+    charge_delegate(delegate, amount)
+}
+
+/// Credit `amount` that `delegate_key` spent for `agent` back to its cap.
+/// `info` must be the delegate's PDA; a revoked delegate has nothing to credit.
+pub fn credit_agent_spend(info: &AccountInfo, agent: &Pubkey, delegate_key: &Pubkey, amount: u64) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"delegate", agent.as_ref(), delegate_key.as_ref()],
+        &crate::ID,
+    );
+    require!(info.key() == expected, TixError::InvalidDelegate);
+    if info.data_is_empty() {
+        return Ok(());
+    }
+    require!(info.owner == &crate::ID, TixError::InvalidDelegate);
+
+    let mut delegate = AgentDelegate::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    refund_delegate(&mut delegate, amount);
+    delegate.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+#[instruction(delegate_key: Pubkey)]
+pub struct AddAgentDelegate<'info> {
+    #[account(
+        seeds = [b"agent", owner.key().as_ref(), agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AIAgent>,
+
+    #[account(
+        init,
+        seeds = [b"delegate", agent.key().as_ref(), delegate_key.as_ref()],
+        bump,
+        payer = owner,
+        space = AgentDelegate::SPACE
+    )]
+    pub delegate: Account<'info, AgentDelegate>,
+
+    #[account(
+        mut,
+        constraint = owner.key() == agent.owner @ TixError::Unauthorized
+    )]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAgentDelegate<'info> {
+    #[account(
+        seeds = [b"agent", owner.key().as_ref(), agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AIAgent>,
+
+    #[account(
+        mut,
+        seeds = [b"delegate", agent.key().as_ref(), delegate.delegate.as_ref()],
+        bump = delegate.bump,
+        close = owner
+    )]
+    pub delegate: Account<'info, AgentDelegate>,
+
+    #[account(
+        mut,
+        constraint = owner.key() == agent.owner @ TixError::Unauthorized
+    )]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_delegate_key: Pubkey)]
+pub struct RotateAgentDelegate<'info> {
+    #[account(
+        seeds = [b"agent", owner.key().as_ref(), agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AIAgent>,
+
+    #[account(
+        mut,
+        seeds = [b"delegate", agent.key().as_ref(), delegate.delegate.as_ref()],
+        bump = delegate.bump,
+        close = owner
+    )]
+    pub delegate: Account<'info, AgentDelegate>,
+
+    #[account(
+        init,
+        seeds = [b"delegate", agent.key().as_ref(), new_delegate_key.as_ref()],
+        bump,
+        payer = owner,
+        space = AgentDelegate::SPACE
+    )]
+    pub new_delegate: Account<'info, AgentDelegate>,
+
+    #[account(
+        mut,
+        constraint = owner.key() == agent.owner @ TixError::Unauthorized
+    )]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_000;
+
+    fn delegate(scopes: u8, spend_cap: u64) -> AgentDelegate {
+        AgentDelegate {
+            agent: Pubkey::new_unique(),
+            delegate: Pubkey::new_unique(),
+            scopes,
+            spend_cap,
+            spent: 0,
+            expires_at: NOW + 100,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn live_in_scope_delegate_passes() {
+        let entry = delegate(AgentDelegate::ALL_SCOPES, 1_000);
+        assert!(check_delegate(&entry, &entry.agent, &entry.delegate, AgentDelegate::SCOPE_BUY_PRIMARY, NOW).is_ok());
+        assert!(check_delegate(&entry, &entry.agent, &entry.delegate, AgentDelegate::SCOPE_BUY_RESALE, NOW).is_ok());
+    }
+
+    #[test]
+    fn delegate_is_bound_to_its_agent_and_key() {
+        let entry = delegate(AgentDelegate::ALL_SCOPES, 1_000);
+        let scope = AgentDelegate::SCOPE_BUY_PRIMARY;
+
+        assert!(check_delegate(&entry, &Pubkey::new_unique(), &entry.delegate, scope, NOW).is_err());
+        assert!(check_delegate(&entry, &entry.agent, &Pubkey::new_unique(), scope, NOW).is_err());
+    }
+
+    #[test]
+    fn delegate_is_limited_to_its_scopes() {
+        let entry = delegate(AgentDelegate::SCOPE_BUY_PRIMARY, 1_000);
+
+        assert!(check_delegate(&entry, &entry.agent, &entry.delegate, AgentDelegate::SCOPE_BUY_PRIMARY, NOW).is_ok());
+        assert!(check_delegate(&entry, &entry.agent, &entry.delegate, AgentDelegate::SCOPE_BUY_RESALE, NOW).is_err());
+    }
+
+    #[test]
+    fn expired_delegate_is_refused() {
+        let entry = delegate(AgentDelegate::ALL_SCOPES, 1_000);
+        let scope = AgentDelegate::SCOPE_BUY_PRIMARY;
+
+        assert!(check_delegate(&entry, &entry.agent, &entry.delegate, scope, entry.expires_at - 1).is_ok());
+        assert!(check_delegate(&entry, &entry.agent, &entry.delegate, scope, entry.expires_at).is_err());
+    }
+
+    #[test]
+    fn delegate_spend_stops_at_cap() {
+        let mut entry = delegate(AgentDelegate::ALL_SCOPES, 1_000);
+
+        charge_delegate(&mut entry, 600).unwrap();
+        charge_delegate(&mut entry, 400).unwrap();
+        assert!(charge_delegate(&mut entry, 1).is_err());
+        assert_eq!(entry.spent, 1_000);
+    }
+
+    #[test]
+    fn refunded_spend_frees_the_cap() {
+        let mut entry = delegate(AgentDelegate::ALL_SCOPES, 1_000);
+
+        charge_delegate(&mut entry, 1_000).unwrap();
+        refund_delegate(&mut entry, 300);
+        assert_eq!(entry.spent, 700);
+        charge_delegate(&mut entry, 300).unwrap();
+
+        refund_delegate(&mut entry, 5_000);
+        assert_eq!(entry.spent, 0);
+    }
+
+    #[test]
+    fn rejects_bad_delegate_params() {
+        assert!(validate_delegate_params(AgentDelegate::SCOPE_BUY_PRIMARY, 1, NOW + 1, NOW).is_ok());
+        assert!(validate_delegate_params(0, 1, NOW + 1, NOW).is_err());
+        assert!(validate_delegate_params(1 << 7, 1, NOW + 1, NOW).is_err());
+        assert!(validate_delegate_params(AgentDelegate::ALL_SCOPES, 0, NOW + 1, NOW).is_err());
+        assert!(validate_delegate_params(AgentDelegate::ALL_SCOPES, 1, NOW, NOW).is_err());
+    }
+}
@@ -1,13 +1,22 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token};
 use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
-use crate::state::{AgentEscrow, AIAgent, Event, TicketTier};
+use crate::state::{
+    AgentDelegate, AgentEscrow, AgentTicketCounter, AIAgent, Event, EventVault, FundingSource,
+    GlobalConfig, PurchaseReceipt, Ticket, TicketStatus, TicketTier, UserTicketCounter,
+};
 use crate::error::TixError;
+use crate::math::{calculate_percentage, split_sale, SaleSplit};
+use crate::instructions::delegate::authorize_agent_spend;
+use crate::instructions::limits::{count_agent_ticket, count_user_ticket};
+use crate::instructions::primary_market::{check_primary_sale, record_primary_sale};
+use crate::instructions::nft::{mint_ticket, ticket_name, TicketMintAccounts, TICKET_SYMBOL};
 
-/// =====================================
-/// INSTRUCTIONS
-/// =====================================
+// =====================================
+// INSTRUCTIONS
+// =====================================
 
 /// Create an escrow account for an agent
 pub fn create_escrow(ctx: Context<CreateEscrow>) -> Result<()> {
@@ -76,9 +85,9 @@ pub fn withdraw_from_escrow(ctx: Context<WithdrawFromEscrow>, amount: u64) -> Re
     Ok(())
 }
 
-/// =====================================
-/// CORE FUNCTION: BUY TICKET WITH ESCROW
-/// =====================================
+// =====================================
+// CORE FUNCTION: BUY TICKET WITH ESCROW
+// =====================================
 
 /// Buy ticket using agent's escrow balance
 pub fn buy_ticket_with_escrow(
@@ -86,51 +95,240 @@ pub fn buy_ticket_with_escrow(
     _tier_id: String,
     _agent_owner: Pubkey, 
 ) -> Result<()> {
-    let escrow_info = ctx.accounts.escrow.to_account_info();
-    let organizer_info = ctx.accounts.organizer.to_account_info();
+    let clock = Clock::get()?;
+    let authority = ctx.accounts.authority.key();
+
+    let (price, split) = EscrowPurchase {
+        event: &mut ctx.accounts.event,
+        tier: &mut ctx.accounts.tier,
+        agent: &mut ctx.accounts.agent,
+        escrow: &mut ctx.accounts.escrow,
+        delegate: ctx.accounts.delegate.as_mut(),
+        vault: &mut ctx.accounts.vault,
+        config: &ctx.accounts.config,
+        treasury: &ctx.accounts.treasury,
+        agent_counter: &mut ctx.accounts.agent_ticket_counter,
+        agent_counter_bump: ctx.bumps.agent_ticket_counter,
+        user_counter: &mut ctx.accounts.user_ticket_counter,
+        user_counter_bump: ctx.bumps.user_ticket_counter,
+    }
+    .execute(&authority, clock.unix_timestamp)?;
+
+    let event = &ctx.accounts.event;
+    let tier = &ctx.accounts.tier;
+    let agent = &ctx.accounts.agent;
+    let receipt = &mut ctx.accounts.receipt;
+
+    receipt.event = event.key();
+    receipt.tier = tier.key();
+    receipt.buyer = agent.owner;
+    receipt.escrow = ctx.accounts.escrow.key();
+    receipt.price = price;
+    receipt.refund_amount = split.held();
+    receipt.organizer_fee = split.organizer_fee;
+    receipt.payer = authority;
+    receipt.purchased_at = clock.unix_timestamp;
+    receipt.slot = clock.slot;
+    receipt.bump = ctx.bumps.receipt;
 
-    let event = &mut ctx.accounts.event;
-    let tier = &mut ctx.accounts.tier;
-    let agent = &mut ctx.accounts.agent;
-    let escrow = &mut ctx.accounts.escrow;
+    msg!("Autonomous purchase successful for agent: {}", agent.agent_id);
+    Ok(())
+}
+
+/// Buy ticket from escrow and mint the NFT to the agent owner atomically
+pub fn buy_and_mint_with_escrow(
+    ctx: Context<BuyAndMintWithEscrow>,
+    _tier_id: String,
+    _agent_owner: Pubkey,
+) -> Result<()> {
     let clock = Clock::get()?;
+    let authority = ctx.accounts.authority.key();
+
+    let (price, split) = EscrowPurchase {
+        event: &mut ctx.accounts.event,
+        tier: &mut ctx.accounts.tier,
+        agent: &mut ctx.accounts.agent,
+        escrow: &mut ctx.accounts.escrow,
+        delegate: ctx.accounts.delegate.as_mut(),
+        vault: &mut ctx.accounts.vault,
+        config: &ctx.accounts.config,
+        treasury: &ctx.accounts.treasury,
+        agent_counter: &mut ctx.accounts.agent_ticket_counter,
+        agent_counter_bump: ctx.bumps.agent_ticket_counter,
+        user_counter: &mut ctx.accounts.user_ticket_counter,
+        user_counter_bump: ctx.bumps.user_ticket_counter,
+    }
+    .execute(&authority, clock.unix_timestamp)?;
+
+    let event = &ctx.accounts.event;
+    let tier = &ctx.accounts.tier;
+
+    ctx.accounts.ticket.set_inner(Ticket {
+        mint: ctx.accounts.ticket_mint.key(),
+        event: event.key(),
+        tier: tier.key(),
+        original_price: price,
+        payment_mint: Pubkey::default(),
+        funding_source: FundingSource::Escrow,
+        funding_account: ctx.accounts.escrow.key(),
+        purchase_price: price,
+        refund_amount: split.held(),
+        organizer_fee: split.organizer_fee,
+        status: TicketStatus::Active,
+        purchased_at: clock.unix_timestamp,
+        validated_at: None,
+        seat_info: None,
+        entry_count: 0,
+        is_inside: false,
+        last_entry_at: 0,
+        last_exit_at: 0,
+        last_checkin_nonce: 0,
+        bump: ctx.bumps.ticket,
+    });
+
+    mint_ticket(
+        TicketMintAccounts {
+            event: &event.to_account_info(),
+            ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+            metadata: &ctx.accounts.metadata.to_account_info(),
+            recipient_token_account: &ctx.accounts.owner_token_account.to_account_info(),
+            payer: &ctx.accounts.authority.to_account_info(),
+            token_program: &ctx.accounts.token_program.to_account_info(),
+            token_metadata_program: &ctx.accounts.token_metadata_program.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+            rent: &ctx.accounts.rent.to_account_info(),
+        },
+        event,
+        ticket_name(event, tier),
+        TICKET_SYMBOL.to_string(),
+        event.metadata_uri.clone(),
+    )?;
+
+    msg!(
+        "Agent {} bought and minted ticket {}",
+        ctx.accounts.agent.agent_id,
+        ctx.accounts.ticket_mint.key()
+    );
+    Ok(())
+}
 
-    require!(event.is_active, TixError::EventNotActive);
-    require!(tier.is_active, TixError::TierNotActive);
-    require!(tier.current_supply < tier.max_supply, TixError::TierSoldOut);
+// =====================================
+// HELPERS
+// =====================================
+
+/// Agent status, escrow balance, total budget and per-ticket cap checks
+/// shared by every escrow-funded purchase
+pub fn check_agent_budget(agent: &AIAgent, escrow: &AgentEscrow, price: u64) -> Result<()> {
     require!(agent.is_active, TixError::AgentInactive);
     require!(agent.auto_purchase_enabled, TixError::AutoPurchaseDisabled);
-
-    let price = tier.price;
     require!(escrow.balance >= price, TixError::InsufficientEscrowBalance);
 
     let remaining_budget = agent.total_budget.checked_sub(agent.spent_budget)
         .ok_or(TixError::InsufficientAgentBudget)?;
-    
+
     require!(price <= remaining_budget, TixError::InsufficientAgentBudget);
     require!(price <= agent.max_budget_per_ticket, TixError::InsufficientAgentBudget);
+    Ok(())
+}
 
-    **escrow_info.try_borrow_mut_lamports()? -= price;
-    **organizer_info.try_borrow_mut_lamports()? += price;
-
-    escrow.balance = escrow.balance.checked_sub(price).ok_or(TixError::MathUnderflow)?;
-    escrow.total_spent = escrow.total_spent.checked_add(price).ok_or(TixError::MathOverflow)?;
-    escrow.last_activity = clock.unix_timestamp;
-
-    agent.spent_budget = agent.spent_budget.checked_add(price).ok_or(TixError::MathOverflow)?;
-    agent.tickets_purchased += 1;
+/// Enforce the agent's auto-purchase threshold (0 = none): `price` may be at
+/// most that share of the tier's reference price. Returns how far under the
+/// reference the purchase is.
+pub fn check_purchase_threshold(agent: &AIAgent, price: u64, reference_price: u64) -> Result<u64> {
+    if agent.auto_purchase_threshold > 0 {
+        let max_price = calculate_percentage(reference_price, agent.auto_purchase_threshold)?;
+        require!(price <= max_price, TixError::PriceAboveThreshold);
+    }
+    Ok(reference_price.saturating_sub(price))
+}
 
-    tier.current_supply += 1;
-    event.total_tickets_sold += 1;
-    event.total_revenue = event.total_revenue.checked_add(price).ok_or(TixError::MathOverflow)?;
+/// Accounts touched by an escrow-funded primary purchase
+pub struct EscrowPurchase<'a, 'info> {
+    pub event: &'a mut Account<'info, Event>,
+    pub tier: &'a mut Account<'info, TicketTier>,
+    pub agent: &'a mut Account<'info, AIAgent>,
+    pub escrow: &'a mut Account<'info, AgentEscrow>,
+    pub delegate: Option<&'a mut Account<'info, AgentDelegate>>,
+    pub vault: &'a mut Account<'info, EventVault>,
+    pub config: &'a GlobalConfig,
+    pub treasury: &'a AccountInfo<'info>,
+    pub agent_counter: &'a mut Account<'info, AgentTicketCounter>,
+    pub agent_counter_bump: u8,
+    pub user_counter: &'a mut Account<'info, UserTicketCounter>,
+    pub user_counter_bump: u8,
+}
 
-    msg!("Autonomous purchase successful for agent: {}", agent.agent_id);
-    Ok(())
+impl EscrowPurchase<'_, '_> {
+    /// Validate, authorize and settle one ticket; returns the price paid and how it was split
+    pub fn execute(self, authority: &Pubkey, now: i64) -> Result<(u64, SaleSplit)> {
+        let EscrowPurchase {
+            event,
+            tier,
+            agent,
+            escrow,
+            delegate,
+            vault,
+            config,
+            treasury,
+            agent_counter,
+            agent_counter_bump,
+            user_counter,
+            user_counter_bump,
+        } = self;
+
+        require!(tier.is_sol_priced(), TixError::WrongPaymentMint);
+        let price = check_primary_sale(event, tier, now)?;
+
+        check_agent_budget(agent, escrow, price)?;
+        let saved = check_purchase_threshold(agent, price, tier.reference_price(now)?)?;
+
+        authorize_agent_spend(
+            agent,
+            delegate,
+            authority,
+            AgentDelegate::SCOPE_BUY_PRIMARY,
+            price,
+            now,
+        )?;
+
+        count_agent_ticket(
+            agent_counter,
+            agent.key(),
+            event.key(),
+            agent_counter_bump,
+            agent.max_tickets_per_event,
+        )?;
+        count_user_ticket(
+            user_counter,
+            agent.owner,
+            event.key(),
+            user_counter_bump,
+            event.max_tickets_per_wallet,
+        )?;
+
+        let split = split_sale(price, config.protocol_fee_bps, event.organizer_fee_bps)?;
+
+        **escrow.to_account_info().try_borrow_mut_lamports()? -= price;
+        **treasury.try_borrow_mut_lamports()? += split.protocol_fee;
+        **vault.to_account_info().try_borrow_mut_lamports()? += split.held();
+
+        escrow.balance = escrow.balance.checked_sub(price).ok_or(TixError::MathUnderflow)?;
+        escrow.total_spent = escrow.total_spent.checked_add(price).ok_or(TixError::MathOverflow)?;
+        escrow.last_activity = now;
+
+        agent.spent_budget = agent.spent_budget.checked_add(price).ok_or(TixError::MathOverflow)?;
+        agent.tickets_purchased += 1;
+        agent.money_saved = agent.money_saved.checked_add(saved).ok_or(TixError::MathOverflow)?;
+
+        record_primary_sale(event, tier, vault, price, &split)?;
+
+        Ok((price, split))
+    }
 }
 
-/// =====================================
-/// CONTEXTS
-/// =====================================
+// =====================================
+// CONTEXTS
+// =====================================
 
 #[derive(Accounts)]
 pub struct CreateEscrow<'info> {
@@ -228,19 +426,137 @@ pub struct BuyTicketWithEscrow<'info> {
 
     #[account(
         mut,
-        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, EventVault>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against config
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Required unless the authority is the agent owner
+    #[account(
+        mut,
+        seeds = [b"delegate", agent.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, AgentDelegate>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"agent_ticket_counter", agent.key().as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = AgentTicketCounter::SPACE
+    )]
+    pub agent_ticket_counter: Box<Account<'info, AgentTicketCounter>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"user_ticket_counter", agent_owner.as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = UserTicketCounter::SPACE
+    )]
+    pub user_ticket_counter: Box<Account<'info, UserTicketCounter>>,
+
+    /// One receipt per sale, keyed by the tier's supply index
+    #[account(
+        init,
+        seeds = [b"receipt", tier.key().as_ref(), tier.current_supply.to_le_bytes().as_ref()],
+        bump,
+        payer = authority,
+        space = PurchaseReceipt::SPACE
     )]
-    /// CHECK: Verified against event
-    pub organizer: UncheckedAccount<'info>,
+    pub receipt: Account<'info, PurchaseReceipt>,
 
+    #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct MintTicketNFT<'info> {
+#[instruction(tier_id: String, agent_owner: Pubkey)]
+pub struct BuyAndMintWithEscrow<'info> {
     #[account(mut)]
-    pub event: Account<'info, Event>,
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"tier", event.key().as_ref(), tier_id.as_bytes()],
+        bump = tier.bump
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent_owner.as_ref(), agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Box<Account<'info, AIAgent>>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", agent.key().as_ref(), agent_owner.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Box<Account<'info, AgentEscrow>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against config
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Required unless the authority is the agent owner
+    #[account(
+        mut,
+        seeds = [b"delegate", agent.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, AgentDelegate>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"agent_ticket_counter", agent.key().as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = AgentTicketCounter::SPACE
+    )]
+    pub agent_ticket_counter: Box<Account<'info, AgentTicketCounter>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"user_ticket_counter", agent_owner.as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = UserTicketCounter::SPACE
+    )]
+    pub user_ticket_counter: Box<Account<'info, UserTicketCounter>>,
 
     #[account(
         init,
@@ -248,23 +564,125 @@ pub struct MintTicketNFT<'info> {
         mint::decimals = 0,
         mint::authority = event,
     )]
-    pub ticket_mint: Account<'info, anchor_spl::token::Mint>,
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Metadata account will be created via CPI
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
 
     #[account(
         init,
         payer = authority,
         associated_token::mint = ticket_mint,
-        associated_token::authority = buyer,
+        associated_token::authority = agent_owner_wallet,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        seeds = [b"ticket", ticket_mint.key().as_ref()],
+        bump,
+        payer = authority,
+        space = Ticket::SPACE
     )]
-    pub buyer_token_account: Account<'info, anchor_spl::token::TokenAccount>,
+    pub ticket: Box<Account<'info, Ticket>>,
 
-    /// CHECK: The user receiving the NFT
-    pub buyer: UncheckedAccount<'info>,
+    #[account(
+        constraint = agent_owner_wallet.key() == agent.owner @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against agent, receives the NFT
+    pub agent_owner_wallet: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
 
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
     pub system_program: Program<'info, System>,
-}
\ No newline at end of file
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent() -> AIAgent {
+        AIAgent {
+            owner: Pubkey::new_unique(),
+            agent_id: "agent".to_string(),
+            name: "Agent".to_string(),
+            is_active: true,
+            auto_purchase_enabled: true,
+            auto_purchase_threshold: 0,
+            max_budget_per_ticket: 1_000,
+            total_budget: 5_000,
+            spent_budget: 4_000,
+            max_tickets_per_event: 0,
+            tickets_purchased: 0,
+            money_saved: 0,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    fn escrow(balance: u64) -> AgentEscrow {
+        AgentEscrow {
+            agent: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            balance,
+            total_deposited: balance,
+            total_withdrawn: 0,
+            total_spent: 0,
+            created_at: 0,
+            last_activity: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn budget_allows_purchase_within_every_limit() {
+        assert!(check_agent_budget(&agent(), &escrow(1_000), 1_000).is_ok());
+    }
+
+    #[test]
+    fn budget_requires_active_auto_purchasing_agent() {
+        let mut inactive = agent();
+        inactive.is_active = false;
+        assert!(check_agent_budget(&inactive, &escrow(1_000), 1).is_err());
+
+        let mut paused = agent();
+        paused.auto_purchase_enabled = false;
+        assert!(check_agent_budget(&paused, &escrow(1_000), 1).is_err());
+    }
+
+    #[test]
+    fn budget_stops_at_balance_remaining_budget_and_per_ticket_cap() {
+        assert!(check_agent_budget(&agent(), &escrow(999), 1_000).is_err());
+
+        let mut spent = agent();
+        spent.spent_budget = 4_500;
+        assert!(check_agent_budget(&spent, &escrow(1_000), 501).is_err());
+        assert!(check_agent_budget(&spent, &escrow(1_000), 500).is_ok());
+
+        let mut generous = agent();
+        generous.spent_budget = 0;
+        assert!(check_agent_budget(&generous, &escrow(5_000), 1_001).is_err());
+    }
+
+    #[test]
+    fn threshold_caps_price_against_reference() {
+        let mut agent = agent();
+        agent.auto_purchase_threshold = 9_000;
+
+        assert_eq!(check_purchase_threshold(&agent, 900, 1_000).unwrap(), 100);
+        assert!(check_purchase_threshold(&agent, 901, 1_000).is_err());
+    }
+
+    #[test]
+    fn no_threshold_only_tracks_savings() {
+        let agent = agent();
+        assert_eq!(check_purchase_threshold(&agent, 800, 1_000).unwrap(), 200);
+        assert_eq!(check_purchase_threshold(&agent, 1_200, 1_000).unwrap(), 0);
+    }
+}
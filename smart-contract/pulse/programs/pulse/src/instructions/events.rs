@@ -0,0 +1,460 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    Event, EventSchedule, EventTokenVault, EventVault, PriceSchedule, ReentryPolicy,
+    ReferencePricing, TicketTier,
+};
+use crate::error::TixError;
+use crate::constraints::validate_event_schedule;
+use crate::pricing::validate_price_schedule;
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// Stop sales on an event without cancelling it
+pub fn pause_event(ctx: Context<ManageEvent>) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+
+    require!(!event.is_cancelled, TixError::EventAlreadyCancelled);
+    require!(event.is_active, TixError::EventNotActive);
+
+    event.is_active = false;
+
+    msg!("Event {} paused", event.event_id);
+    Ok(())
+}
+
+/// Reopen sales on a paused event
+pub fn resume_event(ctx: Context<ManageEvent>) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+
+    require!(!event.is_cancelled, TixError::EventAlreadyCancelled);
+    require!(!event.is_active, TixError::EventAlreadyActive);
+
+    event.is_active = true;
+
+    msg!("Event {} resumed", event.event_id);
+    Ok(())
+}
+
+/// Update mutable event parameters. Fee terms and the fee recipient are frozen
+/// once tickets have sold, and settlement terms can then only become stricter
+/// for the organizer.
+pub fn update_event(
+    ctx: Context<ManageEvent>,
+    organizer_fee_bps: Option<u16>,
+    max_tickets_per_wallet: Option<u32>,
+    metadata_uri: Option<String>,
+    settles_at: Option<i64>,
+    early_release_bps: Option<u16>,
+    fee_recipient: Option<Pubkey>,
+) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+
+    require!(!event.is_cancelled, TixError::EventAlreadyCancelled);
+
+    if let Some(fee_bps) = organizer_fee_bps {
+        require!(fee_bps <= 10000, TixError::InvalidFeeBps);
+        require!(event.total_tickets_sold == 0, TixError::SaleAlreadyStarted);
+        event.organizer_fee_bps = fee_bps;
+    }
+
+    if let Some(recipient) = fee_recipient {
+        require!(event.total_tickets_sold == 0, TixError::SaleAlreadyStarted);
+        event.fee_recipient = recipient;
+    }
+
+    if let Some(max_tickets) = max_tickets_per_wallet {
+        event.max_tickets_per_wallet = max_tickets;
+    }
+
+    if let Some(uri) = metadata_uri {
+        require!(uri.len() <= Event::MAX_URI_LENGTH, TixError::InvalidInput);
+        event.metadata_uri = uri;
+    }
+
+    if let Some(settles_at) = settles_at {
+        require!(settles_at > Clock::get()?.unix_timestamp, TixError::InvalidSettlementTime);
+        require!(
+            event.total_tickets_sold == 0 || settles_at >= event.settles_at,
+            TixError::SaleAlreadyStarted
+        );
+        event.settles_at = settles_at;
+    }
+
+    if let Some(release_bps) = early_release_bps {
+        require!(release_bps <= 10000, TixError::InvalidFeeBps);
+        require!(
+            event.total_tickets_sold == 0 || release_bps <= event.early_release_bps,
+            TixError::SaleAlreadyStarted
+        );
+        event.early_release_bps = release_bps;
+    }
+
+    msg!("Event {} updated", event.event_id);
+    Ok(())
+}
+
+/// Set the organizer's cut of resales and the cap on resale markups over face
+/// value. Once tickets have sold the royalty can only go down. Passing
+/// `Some(None)` as the cap clears it back to the protocol default.
+pub fn set_resale_terms(
+    ctx: Context<ManageEvent>,
+    royalty_bps: Option<u16>,
+    price_cap_bps: Option<Option<u16>>,
+) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+
+    require!(!event.is_cancelled, TixError::EventAlreadyCancelled);
+
+    if let Some(royalty_bps) = royalty_bps {
+        require!(royalty_bps <= 10000, TixError::InvalidFeeBps);
+        require!(
+            event.total_tickets_sold == 0 || royalty_bps <= event.royalty_bps,
+            TixError::SaleAlreadyStarted
+        );
+        event.royalty_bps = royalty_bps;
+    }
+
+    if let Some(price_cap_bps) = price_cap_bps {
+        if let Some(cap) = price_cap_bps {
+            require!(cap <= 10000, TixError::InvalidFeeBps);
+        }
+        event.price_cap_bps = price_cap_bps;
+    }
+
+    msg!("Resale terms updated for event {}", event.event_id);
+    Ok(())
+}
+
+/// Set whether scanned-out tickets may come back in
+pub fn set_reentry_policy(ctx: Context<ManageEvent>, policy: ReentryPolicy) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+
+    require!(!event.is_cancelled, TixError::EventAlreadyCancelled);
+    if let ReentryPolicy::Limited { max_entries } = policy {
+        require!(max_entries > 0, TixError::InvalidInput);
+    }
+
+    event.reentry_policy = policy;
+
+    msg!("Re-entry policy updated for event {}", event.event_id);
+    Ok(())
+}
+
+/// Move the event and its sale window. Tier overrides keep their own times
+/// and only apply where they overlap the new event window. Settlement
+/// follows the new end time but never moves earlier.
+pub fn reschedule_event(ctx: Context<ManageEvent>, schedule: EventSchedule) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+
+    require!(!event.is_cancelled, TixError::EventAlreadyCancelled);
+    validate_event_schedule(&schedule)?;
+
+    let settles_at = event
+        .rescheduled_settlement(schedule.event_end_time)
+        .ok_or(TixError::MathOverflow)?;
+    require!(settles_at > Clock::get()?.unix_timestamp, TixError::InvalidSettlementTime);
+
+    event.set_schedule(&schedule);
+    event.settles_at = settles_at;
+
+    msg!(
+        "Event {} rescheduled: sales {}-{}, event {}-{}",
+        event.event_id,
+        schedule.sale_start_time,
+        schedule.sale_end_time,
+        schedule.event_start_time,
+        schedule.event_end_time
+    );
+    Ok(())
+}
+
+/// Cancel an event permanently; sales stop and cannot be resumed
+pub fn cancel_event(ctx: Context<ManageEvent>, reason_code: u8) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+
+    require!(!event.is_cancelled, TixError::EventAlreadyCancelled);
+    // Refunds are paid from the vaults, so withdrawn proceeds must be put back first
+    require!(event.vaults_drawn == 0, TixError::ProceedsWithdrawn);
+
+    event.is_active = false;
+    event.is_cancelled = true;
+    event.cancel_reason = reason_code;
+    event.cancelled_at = Clock::get()?.unix_timestamp;
+
+    msg!("Event {} cancelled (reason {})", event.event_id, reason_code);
+    Ok(())
+}
+
+/// Cancel a single tier; its sold tickets become refundable and their
+/// proceeds, organizer fees included, are held back in the vault until claimed
+pub fn cancel_ticket_tier(ctx: Context<ManageTier>) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+    let tier = &mut ctx.accounts.tier;
+
+    require!(!tier.is_cancelled, TixError::TierAlreadyCancelled);
+    require!(event.vaults_drawn == 0, TixError::ProceedsWithdrawn);
+
+    // Hold back what is still owed to buyers, in the tier's own currency. The
+    // organizer fees in it go back to buyers rather than the fee recipient;
+    // any already paid out were funded back before cancelling.
+    let reserve = tier.outstanding_revenue().ok_or(TixError::MathUnderflow)?;
+    if tier.is_sol_priced() {
+        let vault = ctx.accounts.vault.as_mut().ok_or(TixError::WrongPaymentMint)?;
+        vault.fees_held = vault.fees_held.saturating_sub(tier.fees_held);
+        event.refund_reserve = event.refund_reserve.checked_add(reserve).ok_or(TixError::MathOverflow)?;
+    } else {
+        let token_vault = ctx.accounts.token_vault.as_mut().ok_or(TixError::WrongPaymentMint)?;
+        token_vault.fees_held = token_vault.fees_held.saturating_sub(tier.fees_held);
+        token_vault.refund_reserve = token_vault.refund_reserve
+            .checked_add(reserve)
+            .ok_or(TixError::MathOverflow)?;
+    }
+
+    tier.is_active = false;
+    tier.is_cancelled = true;
+
+    msg!("Tier {} cancelled", tier.tier_id);
+    Ok(())
+}
+
+/// Change a tier's price. Once the tier has sold, the organizer must pass
+/// `allow_repricing` to confirm; tickets already sold keep their price.
+pub fn update_tier_price(ctx: Context<ManageTier>, new_price: u64, allow_repricing: bool) -> Result<()> {
+    let tier = &mut ctx.accounts.tier;
+
+    require!(!tier.is_cancelled, TixError::TierAlreadyCancelled);
+    require!(tier.current_supply == 0 || allow_repricing, TixError::TierPriceLocked);
+
+    let old_price = tier.price;
+    tier.price = new_price;
+
+    msg!("Tier {} repriced from {} to {}", tier.tier_id, old_price, new_price);
+    Ok(())
+}
+
+/// Replace a tier's price schedule, under the same repricing rule as `update_tier_price`
+pub fn set_tier_price_schedule(
+    ctx: Context<ManageTier>,
+    schedule: PriceSchedule,
+    allow_repricing: bool,
+) -> Result<()> {
+    let tier = &mut ctx.accounts.tier;
+
+    require!(!tier.is_cancelled, TixError::TierAlreadyCancelled);
+    require!(tier.current_supply == 0 || allow_repricing, TixError::TierPriceLocked);
+    validate_price_schedule(&schedule, tier.price)?;
+
+    tier.price_schedule = schedule;
+
+    msg!("Tier {} price schedule updated", tier.tier_id);
+    Ok(())
+}
+
+/// Choose the price agent purchases on this tier are measured against
+pub fn set_reference_pricing(ctx: Context<ManageTier>, reference: ReferencePricing) -> Result<()> {
+    let tier = &mut ctx.accounts.tier;
+
+    require!(!tier.is_cancelled, TixError::TierAlreadyCancelled);
+
+    tier.reference_pricing = reference;
+
+    msg!("Tier {} reference pricing updated", tier.tier_id);
+    Ok(())
+}
+
+/// Grow or shrink a tier's supply; it can never drop below what has sold
+pub fn resize_tier(ctx: Context<ManageTier>, new_max_supply: u64) -> Result<()> {
+    let tier = &mut ctx.accounts.tier;
+
+    require!(!tier.is_cancelled, TixError::TierAlreadyCancelled);
+    require!(new_max_supply > 0, TixError::InvalidSupply);
+    require!(new_max_supply >= tier.current_supply, TixError::InvalidSupply);
+
+    tier.max_supply = new_max_supply;
+
+    msg!("Tier {} resized to {}", tier.tier_id, new_max_supply);
+    Ok(())
+}
+
+/// Disable or re-enable sales on a single tier
+pub fn set_tier_active(ctx: Context<ManageTier>, active: bool) -> Result<()> {
+    let event = &ctx.accounts.event;
+    let tier = &mut ctx.accounts.tier;
+
+    require!(!tier.is_cancelled, TixError::TierAlreadyCancelled);
+    require!(!event.is_cancelled, TixError::EventAlreadyCancelled);
+    require!(tier.is_active != active, TixError::TierStateUnchanged);
+
+    tier.is_active = active;
+
+    msg!("Tier {} {}", tier.tier_id, if active { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Close a tier and reclaim its rent. Unsold tiers can close any time;
+/// tiers with sales only once cancelled or the event is over, and all
+/// refunds are claimed.
+pub fn close_ticket_tier(ctx: Context<CloseTicketTier>) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+    let tier = &ctx.accounts.tier;
+
+    if tier.current_supply > 0 {
+        let event_over = Clock::get()?.unix_timestamp > event.event_end_time;
+        require!(
+            tier.is_cancelled || event.is_cancelled || event_over,
+            TixError::TierNotFinished
+        );
+        require!(!tier.has_pending_refunds(event), TixError::RefundsPending);
+    }
+    require!(tier.waitlist_queued == 0, TixError::WaitlistNotEmpty);
+
+    // A waitlist left behind would block reopening one under the same tier id
+    let waitlist = ctx.accounts.waitlist.to_account_info();
+    if !waitlist.data_is_empty() {
+        let organizer = ctx.accounts.organizer.to_account_info();
+        **organizer.try_borrow_mut_lamports()? += waitlist.lamports();
+        **waitlist.try_borrow_mut_lamports()? = 0;
+        waitlist.assign(&System::id());
+        waitlist.resize(0)?;
+    }
+
+    event.tier_count = event.tier_count.checked_sub(1).ok_or(TixError::MathUnderflow)?;
+
+    msg!("Tier {} closed", tier.tier_id);
+    Ok(())
+}
+
+/// Close a wound-down event and its vault once all its tiers and token vaults are closed
+pub fn close_event(ctx: Context<CloseEvent>) -> Result<()> {
+    let event = &ctx.accounts.event;
+
+    require!(!event.is_active, TixError::EventStillActive);
+    require!(
+        Clock::get()?.unix_timestamp >= event.settles_at,
+        TixError::SettlementPending
+    );
+    require!(!event.has_pending_refunds(), TixError::RefundsPending);
+    require!(event.tier_count == 0, TixError::TiersStillOpen);
+    require!(event.token_vaults == 0, TixError::TokenVaultsStillOpen);
+    require!(ctx.accounts.vault.fees_held == 0, TixError::OrganizerFeesHeld);
+
+    msg!("Event {} closed", event.event_id);
+    Ok(())
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+pub struct ManageEvent<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageTier<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"tier", event.key().as_ref(), tier.tier_id.as_bytes()],
+        bump = tier.bump
+    )]
+    pub tier: Account<'info, TicketTier>,
+
+    /// Event vault; needed to cancel a lamport-priced tier
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Option<Account<'info, EventVault>>,
+
+    /// Token vault for the tier's payment mint; needed to cancel a token-priced tier
+    #[account(
+        mut,
+        seeds = [b"token_vault", event.key().as_ref(), tier.payment_mint.as_ref()],
+        bump = token_vault.bump
+    )]
+    pub token_vault: Option<Account<'info, EventTokenVault>>,
+
+    #[account(
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTicketTier<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"tier", event.key().as_ref(), tier.tier_id.as_bytes()],
+        bump = tier.bump,
+        close = organizer
+    )]
+    pub tier: Account<'info, TicketTier>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist", tier.key().as_ref()],
+        bump
+    )]
+    /// CHECK: The tier's waitlist PDA, closed along with the tier if it was opened
+    pub waitlist: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEvent<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump,
+        close = organizer
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump,
+        close = organizer
+    )]
+    pub vault: Account<'info, EventVault>,
+
+    #[account(
+        mut,
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+}
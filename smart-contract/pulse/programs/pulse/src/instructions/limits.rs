@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AgentTicketCounter, UserTicketCounter};
+use crate::error::TixError;
+
+// =====================================
+// PER-EVENT TICKET CAPS
+// =====================================
+
+/// Count one ticket against an agent's per-event cap (0 = no cap).
+/// Counters are created on first purchase, so their keys are set here.
+pub fn count_agent_ticket(
+    counter: &mut AgentTicketCounter,
+    agent: Pubkey,
+    event: Pubkey,
+    bump: u8,
+    max_tickets: u32,
+) -> Result<()> {
+    if counter.agent == Pubkey::default() {
+        counter.agent = agent;
+        counter.event = event;
+        counter.bump = bump;
+    }
+
+    if max_tickets > 0 {
+        require!(counter.ticket_count < max_tickets, TixError::MaxTicketsPerAgentExceeded);
+    }
+    counter.ticket_count = counter.ticket_count.checked_add(1).ok_or(TixError::MathOverflow)?;
+    Ok(())
+}
+
+/// Count one ticket against a wallet's per-event cap (0 = no cap)
+pub fn count_user_ticket(
+    counter: &mut UserTicketCounter,
+    user: Pubkey,
+    event: Pubkey,
+    bump: u8,
+    max_tickets: u32,
+) -> Result<()> {
+    if counter.user == Pubkey::default() {
+        counter.user = user;
+        counter.event = event;
+        counter.bump = bump;
+    }
+
+    if max_tickets > 0 {
+        require!(counter.ticket_count < max_tickets, TixError::MaxTicketsPerUserExceeded);
+    }
+    counter.ticket_count = counter.ticket_count.checked_add(1).ok_or(TixError::MathOverflow)?;
+    Ok(())
+}
+
+/// Take a refunded ticket off an agent's per-event count
+pub fn release_agent_ticket(counter: &mut AgentTicketCounter, agent: Pubkey, event: Pubkey) -> Result<()> {
+    require!(
+        counter.agent == agent && counter.event == event,
+        TixError::InvalidRefundRecipient
+    );
+    counter.ticket_count = counter.ticket_count.saturating_sub(1);
+    Ok(())
+}
+
+/// Take a refunded ticket off a wallet's per-event count
+pub fn release_user_ticket(counter: &mut UserTicketCounter, user: Pubkey, event: Pubkey) -> Result<()> {
+    require!(
+        counter.user == user && counter.event == event,
+        TixError::InvalidRefundRecipient
+    );
+    counter.ticket_count = counter.ticket_count.saturating_sub(1);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent_counter() -> AgentTicketCounter {
+        AgentTicketCounter { agent: Pubkey::default(), event: Pubkey::default(), ticket_count: 0, bump: 0 }
+    }
+
+    fn user_counter() -> UserTicketCounter {
+        UserTicketCounter { user: Pubkey::default(), event: Pubkey::default(), ticket_count: 0, bump: 0 }
+    }
+
+    #[test]
+    fn first_purchase_sets_counter_keys() {
+        let (agent, event) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut counter = agent_counter();
+
+        count_agent_ticket(&mut counter, agent, event, 254, 0).unwrap();
+        assert_eq!(counter.agent, agent);
+        assert_eq!(counter.event, event);
+        assert_eq!(counter.bump, 254);
+        assert_eq!(counter.ticket_count, 1);
+    }
+
+    #[test]
+    fn agent_cap_stops_at_limit() {
+        let (agent, event) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut counter = agent_counter();
+
+        count_agent_ticket(&mut counter, agent, event, 254, 2).unwrap();
+        count_agent_ticket(&mut counter, agent, event, 254, 2).unwrap();
+        assert!(count_agent_ticket(&mut counter, agent, event, 254, 2).is_err());
+        assert_eq!(counter.ticket_count, 2);
+    }
+
+    #[test]
+    fn wallet_cap_stops_at_limit() {
+        let (user, event) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut counter = user_counter();
+
+        count_user_ticket(&mut counter, user, event, 254, 1).unwrap();
+        assert!(count_user_ticket(&mut counter, user, event, 254, 1).is_err());
+        assert_eq!(counter.ticket_count, 1);
+    }
+
+    #[test]
+    fn zero_cap_is_unlimited() {
+        let (user, event) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut counter = user_counter();
+        counter.ticket_count = 1_000;
+
+        count_user_ticket(&mut counter, user, event, 254, 0).unwrap();
+        assert_eq!(counter.ticket_count, 1_001);
+    }
+
+    #[test]
+    fn released_ticket_frees_a_slot() {
+        let (agent, event) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut counter = agent_counter();
+
+        count_agent_ticket(&mut counter, agent, event, 254, 1).unwrap();
+        release_agent_ticket(&mut counter, agent, event).unwrap();
+        count_agent_ticket(&mut counter, agent, event, 254, 1).unwrap();
+
+        let (user, event) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut counter = user_counter();
+
+        count_user_ticket(&mut counter, user, event, 254, 1).unwrap();
+        release_user_ticket(&mut counter, user, event).unwrap();
+        release_user_ticket(&mut counter, user, event).unwrap();
+        assert_eq!(counter.ticket_count, 0);
+    }
+
+    #[test]
+    fn release_requires_matching_counter() {
+        let (user, event) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut counter = user_counter();
+        count_user_ticket(&mut counter, user, event, 254, 0).unwrap();
+
+        assert!(release_user_ticket(&mut counter, Pubkey::new_unique(), event).is_err());
+        assert!(release_user_ticket(&mut counter, user, Pubkey::new_unique()).is_err());
+        assert_eq!(counter.ticket_count, 1);
+    }
+}
@@ -1,4 +1,28 @@
+pub mod admin;
 pub mod escrow;
+pub mod token_escrow;
+pub mod events;
+pub mod delegate;
+pub mod nft;
+pub mod limits;
+pub mod primary_market;
+pub mod tickets;
+pub mod checkin;
+pub mod secondary_market;
+pub mod waitlist;
+pub mod refunds;
+pub mod proceeds;
 
 // Re-export escrow module
+pub use admin::*;
 pub use escrow::*;
+pub use token_escrow::*;
+pub use events::*;
+pub use delegate::*;
+pub use primary_market::*;
+pub use tickets::*;
+pub use checkin::*;
+pub use secondary_market::*;
+pub use waitlist::*;
+pub use refunds::*;
+pub use proceeds::*;
@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::{create_metadata_accounts_v3, CreateMetadataAccountsV3};
+use anchor_spl::metadata::mpl_token_metadata::{self, types::DataV2};
+use anchor_spl::token::{mint_to, MintTo};
+
+use crate::state::{Event, TicketTier};
+use crate::error::TixError;
+
+pub const TICKET_SYMBOL: &str = "PULSE";
+pub const MAX_TICKET_NAME_LENGTH: usize = 32;
+
+/// Accounts needed to mint one ticket NFT under an event PDA
+pub struct TicketMintAccounts<'a, 'info> {
+    pub event: &'a AccountInfo<'info>,
+    pub ticket_mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub recipient_token_account: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub rent: &'a AccountInfo<'info>,
+}
+
+/// Ticket name derived from on-chain event and tier ids, cut to Metaplex's limit
+pub fn ticket_name(event: &Event, tier: &TicketTier) -> String {
+    let mut name = format!("{} {}", event.event_id, tier.tier_id);
+    if name.len() > MAX_TICKET_NAME_LENGTH {
+        let mut end = MAX_TICKET_NAME_LENGTH;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name.truncate(end);
+    }
+    name
+}
+
+/// Mint a single ticket token and write its Metaplex metadata, signed by the event PDA
+pub fn mint_ticket(
+    accounts: TicketMintAccounts,
+    event: &Event,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    // The metadata CPI is signed with the event seeds, so never hand them to another program
+    require_keys_eq!(
+        accounts.token_metadata_program.key(),
+        mpl_token_metadata::ID,
+        TixError::InvalidMetadataProgram
+    );
+
+    let organizer_key = event.organizer;
+    let bump = [event.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"event",
+        organizer_key.as_ref(),
+        event.event_id.as_bytes(),
+        &bump,
+    ]];
+
+    mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program.clone(),
+            MintTo {
+                mint: accounts.ticket_mint.clone(),
+                to: accounts.recipient_token_account.clone(),
+                authority: accounts.event.clone(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )?;
+
+    let data_v2 = DataV2 {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+    };
+
+    create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            accounts.token_metadata_program.clone(),
+            CreateMetadataAccountsV3 {
+                metadata: accounts.metadata.clone(),
+                mint: accounts.ticket_mint.clone(),
+                mint_authority: accounts.event.clone(),
+                payer: accounts.payer.clone(),
+                update_authority: accounts.event.clone(),
+                system_program: accounts.system_program.clone(),
+                rent: accounts.rent.clone(),
+            },
+            signer_seeds,
+        ),
+        data_v2,
+        true,
+        true,
+        None,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,541 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::state::{
+    Event, EventTokenVault, EventVault, FundingSource, GlobalConfig, Ticket, TicketStatus,
+    TicketTier, UserTicketCounter,
+};
+use crate::error::TixError;
+use crate::constraints::check_sale_window;
+use crate::math::{split_sale, SaleSplit};
+use crate::pricing::effective_price;
+use crate::instructions::limits::count_user_ticket;
+use crate::instructions::nft::{mint_ticket, ticket_name, TicketMintAccounts, TICKET_SYMBOL};
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// Buy a ticket straight from the buyer's wallet and mint the NFT to them
+pub fn buy_ticket(ctx: Context<BuyTicket>, _tier_id: String) -> Result<()> {
+    let buyer_key = ctx.accounts.buyer.key();
+    let event_key = ctx.accounts.event.key();
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(ctx.accounts.tier.is_sol_priced(), TixError::WrongPaymentMint);
+    let price = check_primary_sale(&ctx.accounts.event, &ctx.accounts.tier, now)?;
+
+    count_user_ticket(
+        &mut ctx.accounts.user_ticket_counter,
+        buyer_key,
+        event_key,
+        ctx.bumps.user_ticket_counter,
+        ctx.accounts.event.max_tickets_per_wallet,
+    )?;
+
+    let split = split_sale(
+        price,
+        ctx.accounts.config.protocol_fee_bps,
+        ctx.accounts.event.organizer_fee_bps,
+    )?;
+
+    let buyer_info = ctx.accounts.buyer.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    pay_from_wallet(&system_program, &buyer_info, &ctx.accounts.treasury, split.protocol_fee)?;
+    pay_from_wallet(&system_program, &buyer_info, &ctx.accounts.vault.to_account_info(), split.held())?;
+
+    record_primary_sale(
+        &mut ctx.accounts.event,
+        &mut ctx.accounts.tier,
+        &mut ctx.accounts.vault,
+        price,
+        &split,
+    )?;
+
+    let event = &ctx.accounts.event;
+    let tier = &ctx.accounts.tier;
+
+    ctx.accounts.ticket.set_inner(Ticket {
+        mint: ctx.accounts.ticket_mint.key(),
+        event: event.key(),
+        tier: tier.key(),
+        original_price: price,
+        payment_mint: Pubkey::default(),
+        funding_source: FundingSource::Wallet,
+        funding_account: buyer_key,
+        purchase_price: price,
+        refund_amount: split.held(),
+        organizer_fee: split.organizer_fee,
+        status: TicketStatus::Active,
+        purchased_at: now,
+        validated_at: None,
+        seat_info: None,
+        entry_count: 0,
+        is_inside: false,
+        last_entry_at: 0,
+        last_exit_at: 0,
+        last_checkin_nonce: 0,
+        bump: ctx.bumps.ticket,
+    });
+
+    mint_ticket(
+        TicketMintAccounts {
+            event: &event.to_account_info(),
+            ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+            metadata: &ctx.accounts.metadata.to_account_info(),
+            recipient_token_account: &ctx.accounts.buyer_token_account.to_account_info(),
+            payer: &ctx.accounts.buyer.to_account_info(),
+            token_program: &ctx.accounts.token_program.to_account_info(),
+            token_metadata_program: &ctx.accounts.token_metadata_program.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+            rent: &ctx.accounts.rent.to_account_info(),
+        },
+        event,
+        ticket_name(event, tier),
+        TICKET_SYMBOL.to_string(),
+        event.metadata_uri.clone(),
+    )?;
+
+    msg!("Ticket purchased by {} for {} lamports", buyer_key, price);
+    Ok(())
+}
+
+/// Buy a ticket priced in an SPL token from the buyer's token account
+pub fn buy_ticket_with_token(ctx: Context<BuyTicketWithToken>, _tier_id: String) -> Result<()> {
+    let buyer_key = ctx.accounts.buyer.key();
+    let event_key = ctx.accounts.event.key();
+
+    let now = Clock::get()?.unix_timestamp;
+    let price = check_primary_sale(&ctx.accounts.event, &ctx.accounts.tier, now)?;
+
+    count_user_ticket(
+        &mut ctx.accounts.user_ticket_counter,
+        buyer_key,
+        event_key,
+        ctx.bumps.user_ticket_counter,
+        ctx.accounts.event.max_tickets_per_wallet,
+    )?;
+
+    let split = split_sale(
+        price,
+        ctx.accounts.config.protocol_fee_bps,
+        ctx.accounts.event.organizer_fee_bps,
+    )?;
+
+    TokenSalePayment {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        mint: &ctx.accounts.payment_mint,
+        from: &ctx.accounts.buyer_payment_account.to_account_info(),
+        authority: &ctx.accounts.buyer.to_account_info(),
+        treasury: &ctx.accounts.treasury_token_account.to_account_info(),
+        vault: &ctx.accounts.vault_token_account.to_account_info(),
+    }
+    .pay(&split, &[])?;
+
+    record_token_sale(
+        &mut ctx.accounts.event,
+        &mut ctx.accounts.tier,
+        &mut ctx.accounts.token_vault,
+        price,
+        &split,
+    )?;
+
+    let event = &ctx.accounts.event;
+    let tier = &ctx.accounts.tier;
+
+    ctx.accounts.ticket.set_inner(Ticket {
+        mint: ctx.accounts.ticket_mint.key(),
+        event: event.key(),
+        tier: tier.key(),
+        original_price: price,
+        payment_mint: ctx.accounts.payment_mint.key(),
+        funding_source: FundingSource::Wallet,
+        funding_account: buyer_key,
+        purchase_price: price,
+        refund_amount: split.held(),
+        organizer_fee: split.organizer_fee,
+        status: TicketStatus::Active,
+        purchased_at: now,
+        validated_at: None,
+        seat_info: None,
+        entry_count: 0,
+        is_inside: false,
+        last_entry_at: 0,
+        last_exit_at: 0,
+        last_checkin_nonce: 0,
+        bump: ctx.bumps.ticket,
+    });
+
+    mint_ticket(
+        TicketMintAccounts {
+            event: &event.to_account_info(),
+            ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+            metadata: &ctx.accounts.metadata.to_account_info(),
+            recipient_token_account: &ctx.accounts.buyer_token_account.to_account_info(),
+            payer: &ctx.accounts.buyer.to_account_info(),
+            token_program: &ctx.accounts.token_program.to_account_info(),
+            token_metadata_program: &ctx.accounts.token_metadata_program.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+            rent: &ctx.accounts.rent.to_account_info(),
+        },
+        event,
+        ticket_name(event, tier),
+        TICKET_SYMBOL.to_string(),
+        event.metadata_uri.clone(),
+    )?;
+
+    msg!(
+        "Ticket purchased by {} for {} of mint {}",
+        buyer_key,
+        price,
+        ctx.accounts.payment_mint.key()
+    );
+    Ok(())
+}
+
+/// Quote the price the next ticket in a tier would cost right now, in the
+/// tier's payment currency. Read-only; returned to the caller as return data.
+pub fn quote_ticket_price(ctx: Context<QuoteTicketPrice>, _tier_id: String) -> Result<u64> {
+    let now = Clock::get()?.unix_timestamp;
+    let price = check_primary_sale(&ctx.accounts.event, &ctx.accounts.tier, now)?;
+
+    msg!("Quote for tier {}: {}", ctx.accounts.tier.tier_id, price);
+    Ok(price)
+}
+
+// =====================================
+// SHARED SALE LOGIC
+// =====================================
+
+/// Check the event and tier can sell one more ticket now; returns its effective price
+pub fn check_primary_sale(event: &Event, tier: &TicketTier, now: i64) -> Result<u64> {
+    require!(event.is_active, TixError::EventNotActive);
+    require!(tier.is_active, TixError::TierNotActive);
+    check_sale_window(event, tier, now)?;
+    // Free seats are held for the waitlist first
+    require!(tier.seats_left() > tier.waitlist_queued, TixError::TierSoldOut);
+
+    effective_price(tier, now)
+}
+
+fn record_ticket_sold(event: &mut Event, tier: &mut TicketTier, price: u64, split: &SaleSplit) -> Result<()> {
+    tier.current_supply = tier.current_supply.checked_add(1).ok_or(TixError::MathOverflow)?;
+    tier.revenue = tier.revenue.checked_add(price).ok_or(TixError::MathOverflow)?;
+    tier.collected = tier.collected.checked_add(split.held()).ok_or(TixError::MathOverflow)?;
+    tier.fees_held = tier.fees_held.checked_add(split.organizer_fee).ok_or(TixError::MathOverflow)?;
+    event.total_tickets_sold = event.total_tickets_sold.checked_add(1).ok_or(TixError::MathOverflow)?;
+    Ok(())
+}
+
+/// Record a completed primary sale on the tier, event and proceeds vault.
+/// The caller has already paid the protocol fee and moved `split.held()` into the vault.
+pub fn record_primary_sale(
+    event: &mut Event,
+    tier: &mut TicketTier,
+    vault: &mut EventVault,
+    price: u64,
+    split: &SaleSplit,
+) -> Result<()> {
+    record_ticket_sold(event, tier, price, split)?;
+    event.total_revenue = event.total_revenue.checked_add(price).ok_or(TixError::MathOverflow)?;
+    event.total_protocol_fees = event.total_protocol_fees
+        .checked_add(split.protocol_fee)
+        .ok_or(TixError::MathOverflow)?;
+    event.total_organizer_fees = event.total_organizer_fees
+        .checked_add(split.organizer_fee)
+        .ok_or(TixError::MathOverflow)?;
+    vault.balance = vault.balance.checked_add(split.held()).ok_or(TixError::MathOverflow)?;
+    vault.total_collected = vault.total_collected.checked_add(split.held()).ok_or(TixError::MathOverflow)?;
+    vault.fees_held = vault.fees_held.checked_add(split.organizer_fee).ok_or(TixError::MathOverflow)?;
+    Ok(())
+}
+
+/// Token-priced counterpart of `record_primary_sale`; revenue is kept on the
+/// mint's token vault rather than the lamport totals on the event
+pub fn record_token_sale(
+    event: &mut Event,
+    tier: &mut TicketTier,
+    token_vault: &mut EventTokenVault,
+    price: u64,
+    split: &SaleSplit,
+) -> Result<()> {
+    record_ticket_sold(event, tier, price, split)?;
+    token_vault.total_revenue = token_vault.total_revenue.checked_add(price).ok_or(TixError::MathOverflow)?;
+    token_vault.total_protocol_fees = token_vault.total_protocol_fees
+        .checked_add(split.protocol_fee)
+        .ok_or(TixError::MathOverflow)?;
+    token_vault.total_organizer_fees = token_vault.total_organizer_fees
+        .checked_add(split.organizer_fee)
+        .ok_or(TixError::MathOverflow)?;
+    token_vault.balance = token_vault.balance.checked_add(split.held()).ok_or(TixError::MathOverflow)?;
+    token_vault.total_collected = token_vault.total_collected
+        .checked_add(split.held())
+        .ok_or(TixError::MathOverflow)?;
+    token_vault.fees_held = token_vault.fees_held
+        .checked_add(split.organizer_fee)
+        .ok_or(TixError::MathOverflow)?;
+    Ok(())
+}
+
+/// Token accounts an SPL-token sale pays out of and into
+pub struct TokenSalePayment<'a, 'info> {
+    pub token_program: &'a AccountInfo<'info>,
+    pub mint: &'a Account<'info, Mint>,
+    pub from: &'a AccountInfo<'info>,
+    pub authority: &'a AccountInfo<'info>,
+    pub treasury: &'a AccountInfo<'info>,
+    pub vault: &'a AccountInfo<'info>,
+}
+
+impl<'info> TokenSalePayment<'_, 'info> {
+    /// Pay each part of `split` from `from`; `signer_seeds` is empty when the
+    /// authority signed the transaction itself
+    pub fn pay(&self, split: &SaleSplit, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        self.transfer(self.treasury, split.protocol_fee, signer_seeds)?;
+        self.transfer(self.vault, split.held(), signer_seeds)
+    }
+
+    fn transfer(&self, to: &AccountInfo<'info>, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                TransferChecked {
+                    from: self.from.clone(),
+                    mint: self.mint.to_account_info(),
+                    to: to.clone(),
+                    authority: self.authority.clone(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            self.mint.decimals,
+        )
+    }
+}
+
+/// System transfer from a signing wallet; zero amounts are skipped
+pub fn pay_from_wallet<'info>(
+    system_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            anchor_lang::system_program::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+            },
+        ),
+        amount,
+    )
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+#[instruction(tier_id: String)]
+pub struct BuyTicket<'info> {
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"tier", event.key().as_ref(), tier_id.as_bytes()],
+        bump = tier.bump
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"user_ticket_counter", buyer.key().as_ref(), event.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = UserTicketCounter::SPACE
+    )]
+    pub user_ticket_counter: Box<Account<'info, UserTicketCounter>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against config
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        mint::decimals = 0,
+        mint::authority = event,
+    )]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Metadata account will be created via CPI
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        seeds = [b"ticket", ticket_mint.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = Ticket::SPACE
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier_id: String)]
+pub struct QuoteTicketPrice<'info> {
+    pub event: Account<'info, Event>,
+
+    #[account(
+        seeds = [b"tier", event.key().as_ref(), tier_id.as_bytes()],
+        bump = tier.bump
+    )]
+    pub tier: Account<'info, TicketTier>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier_id: String)]
+pub struct BuyTicketWithToken<'info> {
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"tier", event.key().as_ref(), tier_id.as_bytes()],
+        bump = tier.bump,
+        constraint = !tier.is_sol_priced() @ TixError::WrongPaymentMint
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"user_ticket_counter", buyer.key().as_ref(), event.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = UserTicketCounter::SPACE
+    )]
+    pub user_ticket_counter: Box<Account<'info, UserTicketCounter>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        constraint = payment_mint.key() == tier.payment_mint @ TixError::WrongPaymentMint
+    )]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", event.key().as_ref(), payment_mint.key().as_ref()],
+        bump = token_vault.bump
+    )]
+    pub token_vault: Box<Account<'info, EventTokenVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_account", token_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = buyer
+    )]
+    pub buyer_payment_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = config.treasury
+    )]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        mint::decimals = 0,
+        mint::authority = event,
+    )]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Metadata account will be created via CPI
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        seeds = [b"ticket", ticket_mint.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = Ticket::SPACE
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
@@ -0,0 +1,545 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::state::{Event, EventTokenVault, EventVault};
+use crate::error::TixError;
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// Organizer withdraws sale proceeds from the event vault. Before the
+/// settlement time only the configured early-release share is available;
+/// funds reserved for cancelled tiers are never released.
+pub fn withdraw_proceeds(ctx: Context<WithdrawProceeds>, amount: u64) -> Result<()> {
+    require!(amount > 0, TixError::InvalidInput);
+
+    let now = Clock::get()?.unix_timestamp;
+    let event = &ctx.accounts.event;
+    let vault_info = ctx.accounts.vault.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+
+    require!(
+        ctx.accounts.vault.is_reconciled(event, vault_info.lamports(), rent_exempt),
+        TixError::VaultOutOfBalance
+    );
+    require!(!event.has_pending_refunds(), TixError::RefundsPending);
+
+    let available = withdrawable_proceeds(event, &ctx.accounts.vault, now);
+    if now < event.settles_at {
+        require!(amount <= available, TixError::SettlementPending);
+    } else {
+        require!(amount <= available, TixError::InsufficientVaultBalance);
+    }
+
+    **vault_info.try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.organizer.to_account_info().try_borrow_mut_lamports()? += amount;
+
+    let vault = &mut ctx.accounts.vault;
+    let was_drawn = vault.is_drawn();
+    vault.balance = vault.balance.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    vault.total_withdrawn = vault.total_withdrawn.checked_add(amount).ok_or(TixError::MathOverflow)?;
+
+    let event = &mut ctx.accounts.event;
+    track_drawn_vault(event, was_drawn, vault.is_drawn())?;
+
+    msg!("Withdrew {} lamports of proceeds for event {}", amount, event.event_id);
+    Ok(())
+}
+
+/// Organizer opens the proceeds vault for a payment mint. Required before
+/// tiers priced in that mint can sell.
+pub fn open_token_vault(ctx: Context<OpenTokenVault>) -> Result<()> {
+    let event = &mut ctx.accounts.event;
+    event.token_vaults = event.token_vaults.checked_add(1).ok_or(TixError::MathOverflow)?;
+
+    let token_vault = &mut ctx.accounts.token_vault;
+
+    token_vault.event = ctx.accounts.event.key();
+    token_vault.mint = ctx.accounts.payment_mint.key();
+    token_vault.balance = 0;
+    token_vault.total_funded = 0;
+    token_vault.total_revenue = 0;
+    token_vault.total_protocol_fees = 0;
+    token_vault.total_organizer_fees = 0;
+    token_vault.total_collected = 0;
+    token_vault.total_refunded = 0;
+    token_vault.total_withdrawn = 0;
+    token_vault.refund_reserve = 0;
+    token_vault.fees_held = 0;
+    token_vault.total_fees_paid = 0;
+    token_vault.bump = ctx.bumps.token_vault;
+
+    msg!(
+        "Token vault for mint {} opened for event {}",
+        token_vault.mint,
+        ctx.accounts.event.event_id
+    );
+    Ok(())
+}
+
+/// Token-priced counterpart of `withdraw_proceeds`, under the same settlement rules
+pub fn withdraw_token_proceeds(ctx: Context<WithdrawTokenProceeds>, amount: u64) -> Result<()> {
+    require!(amount > 0, TixError::InvalidInput);
+
+    let now = Clock::get()?.unix_timestamp;
+    let event = &ctx.accounts.event;
+    let token_vault = &ctx.accounts.token_vault;
+
+    require!(
+        token_vault.is_reconciled(ctx.accounts.vault_token_account.amount),
+        TixError::VaultOutOfBalance
+    );
+    require!(!event.has_pending_refunds(), TixError::RefundsPending);
+
+    let available = withdrawable_token_proceeds(event, token_vault, now);
+    if now < event.settles_at {
+        require!(amount <= available, TixError::SettlementPending);
+    } else {
+        require!(amount <= available, TixError::InsufficientVaultBalance);
+    }
+
+    let bump = [token_vault.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"token_vault",
+        token_vault.event.as_ref(),
+        token_vault.mint.as_ref(),
+        &bump,
+    ]];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.organizer_token_account.to_account_info(),
+                authority: ctx.accounts.token_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+
+    let token_vault = &mut ctx.accounts.token_vault;
+    let was_drawn = token_vault.is_drawn();
+    token_vault.balance = token_vault.balance.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    token_vault.total_withdrawn = token_vault.total_withdrawn.checked_add(amount).ok_or(TixError::MathOverflow)?;
+
+    track_drawn_vault(&mut ctx.accounts.event, was_drawn, token_vault.is_drawn())?;
+
+    msg!(
+        "Withdrew {} of mint {} in proceeds for event {}",
+        amount,
+        token_vault.mint,
+        ctx.accounts.event.event_id
+    );
+    Ok(())
+}
+
+/// Pay the organizer fees held in the event vault to the event's fee
+/// recipient. They are held until settlement so refunds can return them.
+pub fn release_organizer_fees(ctx: Context<ReleaseOrganizerFees>) -> Result<()> {
+    let event = &ctx.accounts.event;
+    let vault_info = ctx.accounts.vault.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+
+    require!(Clock::get()?.unix_timestamp >= event.settles_at, TixError::SettlementPending);
+    require!(!event.has_pending_refunds(), TixError::RefundsPending);
+    require!(
+        ctx.accounts.vault.is_reconciled(event, vault_info.lamports(), rent_exempt),
+        TixError::VaultOutOfBalance
+    );
+
+    let amount = ctx.accounts.vault.fees_held;
+    require!(amount > 0, TixError::InvalidInput);
+
+    **vault_info.try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.fee_recipient.to_account_info().try_borrow_mut_lamports()? += amount;
+
+    let vault = &mut ctx.accounts.vault;
+    let was_drawn = vault.is_drawn();
+    vault.balance = vault.balance.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    vault.fees_held = 0;
+    vault.total_fees_paid = vault.total_fees_paid.checked_add(amount).ok_or(TixError::MathOverflow)?;
+
+    let event = &mut ctx.accounts.event;
+    track_drawn_vault(event, was_drawn, vault.is_drawn())?;
+
+    msg!("Released {} lamports of organizer fees for event {}", amount, event.event_id);
+    Ok(())
+}
+
+/// Token-priced counterpart of `release_organizer_fees`
+pub fn release_token_organizer_fees(ctx: Context<ReleaseTokenOrganizerFees>) -> Result<()> {
+    let event = &ctx.accounts.event;
+    let token_vault = &ctx.accounts.token_vault;
+
+    require!(Clock::get()?.unix_timestamp >= event.settles_at, TixError::SettlementPending);
+    require!(!event.has_pending_refunds(), TixError::RefundsPending);
+    require!(
+        token_vault.is_reconciled(ctx.accounts.vault_token_account.amount),
+        TixError::VaultOutOfBalance
+    );
+
+    let amount = token_vault.fees_held;
+    require!(amount > 0, TixError::InvalidInput);
+
+    let bump = [token_vault.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"token_vault",
+        token_vault.event.as_ref(),
+        token_vault.mint.as_ref(),
+        &bump,
+    ]];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.fee_recipient_token_account.to_account_info(),
+                authority: ctx.accounts.token_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+
+    let token_vault = &mut ctx.accounts.token_vault;
+    let was_drawn = token_vault.is_drawn();
+    token_vault.balance = token_vault.balance.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    token_vault.fees_held = 0;
+    token_vault.total_fees_paid = token_vault.total_fees_paid
+        .checked_add(amount)
+        .ok_or(TixError::MathOverflow)?;
+
+    track_drawn_vault(&mut ctx.accounts.event, was_drawn, token_vault.is_drawn())?;
+
+    msg!(
+        "Released {} of mint {} in organizer fees for event {}",
+        amount,
+        token_vault.mint,
+        ctx.accounts.event.event_id
+    );
+    Ok(())
+}
+
+/// Close a wound-down event's token vault once its proceeds are fully paid
+/// out. Tokens sent to the vault outside the ledger go to the organizer.
+pub fn close_token_vault(ctx: Context<CloseTokenVault>) -> Result<()> {
+    let event = &ctx.accounts.event;
+    let token_vault = &ctx.accounts.token_vault;
+
+    require!(!event.is_active, TixError::EventStillActive);
+    require!(!event.has_pending_refunds(), TixError::RefundsPending);
+    require!(token_vault.balance == 0, TixError::TokenVaultNotEmpty);
+
+    let bump = [token_vault.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"token_vault",
+        token_vault.event.as_ref(),
+        token_vault.mint.as_ref(),
+        &bump,
+    ]];
+
+    let stray = ctx.accounts.vault_token_account.amount;
+    if stray > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    mint: ctx.accounts.payment_mint.to_account_info(),
+                    to: ctx.accounts.organizer_token_account.to_account_info(),
+                    authority: ctx.accounts.token_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            stray,
+            ctx.accounts.payment_mint.decimals,
+        )?;
+    }
+
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.vault_token_account.to_account_info(),
+            destination: ctx.accounts.organizer.to_account_info(),
+            authority: ctx.accounts.token_vault.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    let event = &mut ctx.accounts.event;
+    event.token_vaults = event.token_vaults.checked_sub(1).ok_or(TixError::MathUnderflow)?;
+
+    msg!(
+        "Token vault for mint {} closed for event {}",
+        ctx.accounts.token_vault.mint,
+        event.event_id
+    );
+    Ok(())
+}
+
+// =====================================
+// HELPERS
+// =====================================
+
+/// Amount the organizer may take out of the vault right now. Held organizer
+/// fees belong to the fee recipient and never count as proceeds.
+pub fn withdrawable_proceeds(event: &Event, vault: &EventVault, now: i64) -> u64 {
+    releasable(
+        event,
+        vault.balance.saturating_sub(event.refund_reserve).saturating_sub(vault.fees_held),
+        vault.total_collected.saturating_sub(event.total_organizer_fees),
+        vault.total_withdrawn,
+        now,
+    )
+}
+
+/// Amount the organizer may take out of a token vault right now
+pub fn withdrawable_token_proceeds(event: &Event, token_vault: &EventTokenVault, now: i64) -> u64 {
+    releasable(
+        event,
+        token_vault.balance
+            .saturating_sub(token_vault.refund_reserve)
+            .saturating_sub(token_vault.fees_held),
+        token_vault.total_collected.saturating_sub(token_vault.total_organizer_fees),
+        token_vault.total_withdrawn,
+        now,
+    )
+}
+
+/// Keep `Event::vaults_drawn` in step as a vault's withdrawals pass what the
+/// organizer has funded back, in either direction
+pub fn track_drawn_vault(event: &mut Event, was_drawn: bool, is_drawn: bool) -> Result<()> {
+    match (was_drawn, is_drawn) {
+        (false, true) => {
+            event.vaults_drawn = event.vaults_drawn.checked_add(1).ok_or(TixError::MathOverflow)?;
+        }
+        (true, false) => {
+            event.vaults_drawn = event.vaults_drawn.checked_sub(1).ok_or(TixError::MathUnderflow)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn releasable(event: &Event, unreserved: u64, total_collected: u64, total_withdrawn: u64, now: i64) -> u64 {
+    if now >= event.settles_at {
+        return unreserved;
+    }
+
+    let released = (total_collected as u128 * event.early_release_bps as u128 / 10000) as u64;
+    unreserved.min(released.saturating_sub(total_withdrawn))
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+pub struct WithdrawProceeds<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, EventVault>,
+
+    #[account(
+        mut,
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenTokenVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [b"token_vault", event.key().as_ref(), payment_mint.key().as_ref()],
+        bump,
+        payer = organizer,
+        space = EventTokenVault::SPACE
+    )]
+    pub token_vault: Account<'info, EventTokenVault>,
+
+    #[account(
+        init,
+        seeds = [b"token_vault_account", token_vault.key().as_ref()],
+        bump,
+        payer = organizer,
+        token::mint = payment_mint,
+        token::authority = token_vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTokenProceeds<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", event.key().as_ref(), payment_mint.key().as_ref()],
+        bump = token_vault.bump
+    )]
+    pub token_vault: Account<'info, EventTokenVault>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_account", token_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = organizer
+    )]
+    pub organizer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseOrganizerFees<'info> {
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, EventVault>,
+
+    #[account(
+        mut,
+        constraint = fee_recipient.key() == event.fee_recipient @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against event
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    /// Anyone may crank the release; fees can only go to the event's fee recipient
+    pub keeper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseTokenOrganizerFees<'info> {
+    #[account(mut)]
+    pub event: Account<'info, Event>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", event.key().as_ref(), payment_mint.key().as_ref()],
+        bump = token_vault.bump
+    )]
+    pub token_vault: Account<'info, EventTokenVault>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_account", token_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = event.fee_recipient
+    )]
+    pub fee_recipient_token_account: Account<'info, TokenAccount>,
+
+    /// Anyone may crank the release; fees can only go to the event's fee recipient
+    pub keeper: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTokenVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", event.key().as_ref(), payment_mint.key().as_ref()],
+        bump = token_vault.bump,
+        close = organizer
+    )]
+    pub token_vault: Account<'info, EventTokenVault>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_account", token_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = organizer
+    )]
+    pub organizer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,646 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, transfer_checked, Burn, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::state::{
+    AgentEscrow, AgentTicketCounter, AIAgent, Event, EventTokenVault, EventVault, FundingSource,
+    Ticket, TicketStatus, TicketTier, TokenEscrow, UserTicketCounter,
+};
+use crate::error::TixError;
+use crate::instructions::limits::{release_agent_ticket, release_user_ticket};
+use crate::instructions::proceeds::track_drawn_vault;
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// Organizer tops up the event vault when proceeds alone cannot cover refunds
+pub fn fund_refund_vault(ctx: Context<FundRefundVault>, amount: u64) -> Result<()> {
+    require!(amount > 0, TixError::InvalidInput);
+
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.organizer.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    let was_drawn = vault.is_drawn();
+    vault.balance = vault.balance.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    vault.total_funded = vault.total_funded.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    track_drawn_vault(&mut ctx.accounts.event, was_drawn, vault.is_drawn())?;
+
+    msg!("Funded {} lamports into vault for event {}", amount, ctx.accounts.event.event_id);
+    Ok(())
+}
+
+/// Holder claims the refund for a cancelled ticket; the NFT is burned and what
+/// the vault received for it goes back to whoever paid (buyer wallet or agent escrow).
+/// That is the price less the protocol fee, which is not refunded.
+pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.ticket_mint.to_account_info(),
+                from: ctx.accounts.holder_token_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+
+    let amount = match ctx.accounts.ticket.funding_source {
+        FundingSource::Wallet => {
+            let wallet = ctx
+                .accounts
+                .refund_wallet
+                .as_ref()
+                .ok_or(TixError::InvalidRefundRecipient)?;
+            require!(
+                wallet.key() == ctx.accounts.ticket.funding_account,
+                TixError::InvalidRefundRecipient
+            );
+            settle_refund(
+                &mut ctx.accounts.event,
+                &mut ctx.accounts.tier,
+                &mut ctx.accounts.ticket,
+                &mut ctx.accounts.vault,
+                &wallet.to_account_info(),
+            )?
+        }
+        FundingSource::Escrow => {
+            let escrow = ctx
+                .accounts
+                .refund_escrow
+                .as_mut()
+                .ok_or(TixError::InvalidRefundRecipient)?;
+            let amount = settle_refund(
+                &mut ctx.accounts.event,
+                &mut ctx.accounts.tier,
+                &mut ctx.accounts.ticket,
+                &mut ctx.accounts.vault,
+                &escrow.to_account_info(),
+            )?;
+            credit_escrow(
+                escrow,
+                ctx.accounts.refund_agent.as_mut().ok_or(TixError::InvalidRefundRecipient)?,
+                ctx.accounts.agent_ticket_counter.as_mut().ok_or(TixError::InvalidRefundRecipient)?,
+                ctx.accounts.user_ticket_counter.as_mut().ok_or(TixError::InvalidRefundRecipient)?,
+                &ctx.accounts.ticket,
+                amount,
+            )?;
+            amount
+        }
+    };
+
+    msg!("Refunded {} lamports for ticket {}", amount, ctx.accounts.ticket_mint.key());
+    Ok(())
+}
+
+/// Keeper claims the refund for an escrow-funded ticket still held by the
+/// agent owner. The ticket is voided rather than burned.
+pub fn claim_escrow_refund(ctx: Context<ClaimEscrowRefund>) -> Result<()> {
+    let escrow_info = ctx.accounts.escrow.to_account_info();
+
+    let amount = settle_refund(
+        &mut ctx.accounts.event,
+        &mut ctx.accounts.tier,
+        &mut ctx.accounts.ticket,
+        &mut ctx.accounts.vault,
+        &escrow_info,
+    )?;
+    credit_escrow(
+        &mut ctx.accounts.escrow,
+        &mut ctx.accounts.agent,
+        &mut ctx.accounts.agent_ticket_counter,
+        &mut ctx.accounts.user_ticket_counter,
+        &ctx.accounts.ticket,
+        amount,
+    )?;
+
+    msg!(
+        "Refunded {} lamports to escrow for ticket {}",
+        amount,
+        ctx.accounts.ticket.mint
+    );
+    Ok(())
+}
+
+/// Organizer tops up a token vault when proceeds alone cannot cover refunds
+pub fn fund_token_refund_vault(ctx: Context<FundTokenRefundVault>, amount: u64) -> Result<()> {
+    require!(amount > 0, TixError::InvalidInput);
+
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.organizer_token_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.organizer.to_account_info(),
+            },
+        ),
+        amount,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+
+    let token_vault = &mut ctx.accounts.token_vault;
+    let was_drawn = token_vault.is_drawn();
+    token_vault.balance = token_vault.balance.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    token_vault.total_funded = token_vault.total_funded.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    track_drawn_vault(&mut ctx.accounts.event, was_drawn, token_vault.is_drawn())?;
+
+    msg!(
+        "Funded {} of mint {} into token vault for event {}",
+        amount,
+        token_vault.mint,
+        ctx.accounts.event.event_id
+    );
+    Ok(())
+}
+
+/// Holder claims the refund for a cancelled token-priced ticket. The NFT is
+/// burned and the price goes back to the buyer's token account for the
+/// payment mint, or to the agent's token escrow.
+pub fn claim_token_refund(ctx: Context<ClaimTokenRefund>) -> Result<()> {
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.ticket_mint.to_account_info(),
+                from: ctx.accounts.holder_token_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+
+    let recipient = ctx.accounts.refund_token_account.to_account_info();
+    let funding_account = ctx.accounts.ticket.funding_account;
+
+    let amount = match ctx.accounts.ticket.funding_source {
+        FundingSource::Wallet => {
+            require!(
+                ctx.accounts.refund_token_account.owner == funding_account,
+                TixError::InvalidRefundRecipient
+            );
+            settle_token_refund(ctx.accounts, &recipient)?
+        }
+        FundingSource::Escrow => {
+            let escrow_key = ctx
+                .accounts
+                .refund_escrow
+                .as_ref()
+                .ok_or(TixError::InvalidRefundRecipient)?
+                .key();
+            let (escrow_token_account, _) = Pubkey::find_program_address(
+                &[b"token_escrow_account", escrow_key.as_ref()],
+                &crate::ID,
+            );
+            require!(
+                recipient.key() == escrow_token_account,
+                TixError::InvalidRefundRecipient
+            );
+
+            let amount = settle_token_refund(ctx.accounts, &recipient)?;
+            credit_token_escrow(
+                ctx.accounts.refund_escrow.as_mut().ok_or(TixError::InvalidRefundRecipient)?,
+                ctx.accounts.agent_ticket_counter.as_mut().ok_or(TixError::InvalidRefundRecipient)?,
+                ctx.accounts.user_ticket_counter.as_mut().ok_or(TixError::InvalidRefundRecipient)?,
+                &ctx.accounts.ticket,
+                amount,
+            )?;
+            amount
+        }
+    };
+
+    msg!(
+        "Refunded {} of mint {} for ticket {}",
+        amount,
+        ctx.accounts.payment_mint.key(),
+        ctx.accounts.ticket_mint.key()
+    );
+    Ok(())
+}
+
+// =====================================
+// HELPERS
+// =====================================
+
+/// Move what the vault received for a ticket to `recipient` and void the ticket
+fn settle_refund<'info>(
+    event: &mut Account<'info, Event>,
+    tier: &mut Account<'info, TicketTier>,
+    ticket: &mut Account<'info, Ticket>,
+    vault: &mut Account<'info, EventVault>,
+    recipient: &AccountInfo<'info>,
+) -> Result<u64> {
+    require!(ticket.payment_mint == Pubkey::default(), TixError::WrongPaymentMint);
+
+    let amount = void_ticket(event, tier, ticket)?;
+    require!(vault.balance >= amount, TixError::InsufficientVaultBalance);
+
+    **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **recipient.try_borrow_mut_lamports()? += amount;
+
+    vault.balance = vault.balance.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    vault.total_refunded = vault.total_refunded.checked_add(amount).ok_or(TixError::MathOverflow)?;
+
+    // A cancelled tier's fees already moved into the reserve
+    if tier.is_cancelled {
+        event.refund_reserve = event.refund_reserve.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    } else {
+        vault.fees_held = vault.fees_held.saturating_sub(ticket.organizer_fee);
+    }
+
+    Ok(amount)
+}
+
+/// Token-priced counterpart of `settle_refund`; pays out of the token vault's account
+fn settle_token_refund<'info>(
+    accounts: &mut ClaimTokenRefund<'info>,
+    recipient: &AccountInfo<'info>,
+) -> Result<u64> {
+    let amount = void_ticket(&mut accounts.event, &mut accounts.tier, &mut accounts.ticket)?;
+    require!(accounts.token_vault.balance >= amount, TixError::InsufficientVaultBalance);
+
+    let token_vault = &accounts.token_vault;
+    let bump = [token_vault.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"token_vault",
+        token_vault.event.as_ref(),
+        token_vault.mint.as_ref(),
+        &bump,
+    ]];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: accounts.vault_token_account.to_account_info(),
+                mint: accounts.payment_mint.to_account_info(),
+                to: recipient.clone(),
+                authority: accounts.token_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        accounts.payment_mint.decimals,
+    )?;
+
+    let token_vault = &mut accounts.token_vault;
+    token_vault.balance = token_vault.balance.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    token_vault.total_refunded = token_vault.total_refunded.checked_add(amount).ok_or(TixError::MathOverflow)?;
+
+    if accounts.tier.is_cancelled {
+        token_vault.refund_reserve = token_vault.refund_reserve
+            .checked_sub(amount)
+            .ok_or(TixError::MathUnderflow)?;
+    } else {
+        token_vault.fees_held = token_vault.fees_held.saturating_sub(accounts.ticket.organizer_fee);
+    }
+
+    Ok(amount)
+}
+
+/// Mark a refundable ticket refunded and update the tier and event counts;
+/// returns the amount owed
+fn void_ticket(event: &mut Event, tier: &mut TicketTier, ticket: &mut Ticket) -> Result<u64> {
+    // Consumed tickets are refunded too: a cancellation refunds everyone who paid
+    match ticket.status {
+        TicketStatus::Active | TicketStatus::Consumed => {}
+        TicketStatus::Refunded => return err!(TixError::TicketAlreadyRefunded),
+    }
+    require!(tier.is_refundable(event), TixError::NotRefundable);
+
+    let amount = ticket.refund_amount;
+
+    ticket.status = TicketStatus::Refunded;
+    tier.tickets_refunded = tier.tickets_refunded.checked_add(1).ok_or(TixError::MathOverflow)?;
+    tier.refunded_amount = tier.refunded_amount.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    tier.fees_held = tier.fees_held.checked_sub(ticket.organizer_fee).ok_or(TixError::MathUnderflow)?;
+    event.tickets_refunded = event.tickets_refunded.checked_add(1).ok_or(TixError::MathOverflow)?;
+
+    Ok(amount)
+}
+
+/// Return a refund to the agent escrow that paid for the ticket. Spending and
+/// budget are unwound by at most what the escrow paid, which a cheap resale can
+/// put below the refund, and the ticket stops counting against the agent's and
+/// owner's per-event caps.
+fn credit_escrow(
+    escrow: &mut AgentEscrow,
+    agent: &mut Account<AIAgent>,
+    agent_counter: &mut AgentTicketCounter,
+    user_counter: &mut UserTicketCounter,
+    ticket: &Ticket,
+    amount: u64,
+) -> Result<()> {
+    require!(agent.key() == escrow.agent, TixError::InvalidRefundRecipient);
+    release_agent_ticket(agent_counter, escrow.agent, ticket.event)?;
+    release_user_ticket(user_counter, escrow.owner, ticket.event)?;
+
+    let unspent = amount.min(ticket.purchase_price);
+
+    escrow.balance = escrow.balance.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    escrow.total_spent = escrow.total_spent.checked_sub(unspent).ok_or(TixError::MathUnderflow)?;
+    escrow.last_activity = Clock::get()?.unix_timestamp;
+
+    agent.spent_budget = agent.spent_budget.checked_sub(unspent).ok_or(TixError::MathUnderflow)?;
+    Ok(())
+}
+
+/// Token-priced counterpart of `credit_escrow`. Token spending does not draw
+/// on the agent's lamport budget, so only the escrow and the caps are unwound.
+fn credit_token_escrow(
+    escrow: &mut TokenEscrow,
+    agent_counter: &mut AgentTicketCounter,
+    user_counter: &mut UserTicketCounter,
+    ticket: &Ticket,
+    amount: u64,
+) -> Result<()> {
+    release_agent_ticket(agent_counter, escrow.agent, ticket.event)?;
+    release_user_ticket(user_counter, escrow.owner, ticket.event)?;
+
+    let unspent = amount.min(ticket.purchase_price);
+
+    escrow.balance = escrow.balance.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    escrow.total_spent = escrow.total_spent.checked_sub(unspent).ok_or(TixError::MathUnderflow)?;
+    escrow.last_activity = Clock::get()?.unix_timestamp;
+    Ok(())
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+pub struct FundRefundVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, EventVault>,
+
+    #[account(
+        mut,
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        constraint = tier.key() == ticket.tier @ TixError::NotRefundable
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket_mint.key().as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.event == event.key() @ TixError::NotRefundable
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(mut)]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = ticket.is_held_by(&holder_token_account, &holder.key()) @ TixError::TicketNotHeld
+    )]
+    pub holder_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    /// Original buyer wallet, for wallet-funded tickets
+    #[account(mut)]
+    /// CHECK: Verified against ticket.funding_account in handler
+    pub refund_wallet: Option<UncheckedAccount<'info>>,
+
+    /// Original agent escrow, for escrow-funded tickets
+    #[account(
+        mut,
+        constraint = refund_escrow.key() == ticket.funding_account @ TixError::InvalidRefundRecipient
+    )]
+    pub refund_escrow: Option<Box<Account<'info, AgentEscrow>>>,
+
+    /// Agent behind the refund escrow; its budget is given back
+    #[account(mut)]
+    pub refund_agent: Option<Box<Account<'info, AIAgent>>>,
+
+    /// Escrow refunds only: the agent's and owner's per-event counters
+    #[account(mut)]
+    pub agent_ticket_counter: Option<Box<Account<'info, AgentTicketCounter>>>,
+
+    #[account(mut)]
+    pub user_ticket_counter: Option<Box<Account<'info, UserTicketCounter>>>,
+
+    pub holder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimEscrowRefund<'info> {
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        constraint = tier.key() == ticket.tier @ TixError::NotRefundable
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket.mint.as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.event == event.key() @ TixError::NotRefundable,
+        constraint = ticket.funding_source == FundingSource::Escrow @ TixError::InvalidRefundRecipient
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        constraint = ticket.is_held_by(&owner_token_account, &escrow.owner) @ TixError::TicketNotHeld
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.agent.as_ref(), escrow.owner.as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.key() == ticket.funding_account @ TixError::InvalidRefundRecipient
+    )]
+    pub escrow: Box<Account<'info, AgentEscrow>>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", escrow.owner.as_ref(), agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = agent.key() == escrow.agent @ TixError::InvalidRefundRecipient
+    )]
+    pub agent: Box<Account<'info, AIAgent>>,
+
+    #[account(
+        mut,
+        seeds = [b"agent_ticket_counter", escrow.agent.as_ref(), event.key().as_ref()],
+        bump = agent_ticket_counter.bump
+    )]
+    pub agent_ticket_counter: Box<Account<'info, AgentTicketCounter>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_ticket_counter", escrow.owner.as_ref(), event.key().as_ref()],
+        bump = user_ticket_counter.bump
+    )]
+    pub user_ticket_counter: Box<Account<'info, UserTicketCounter>>,
+
+    /// Anyone may crank escrow refunds; funds can only flow back to the escrow
+    pub keeper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundTokenRefundVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", event.key().as_ref(), payment_mint.key().as_ref()],
+        bump = token_vault.bump
+    )]
+    pub token_vault: Account<'info, EventTokenVault>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_account", token_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = organizer
+    )]
+    pub organizer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTokenRefund<'info> {
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        constraint = tier.key() == ticket.tier @ TixError::NotRefundable
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket_mint.key().as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.event == event.key() @ TixError::NotRefundable
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(mut)]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = ticket.is_held_by(&holder_token_account, &holder.key()) @ TixError::TicketNotHeld
+    )]
+    pub holder_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = payment_mint.key() == ticket.payment_mint @ TixError::WrongPaymentMint
+    )]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", event.key().as_ref(), payment_mint.key().as_ref()],
+        bump = token_vault.bump
+    )]
+    pub token_vault: Box<Account<'info, EventTokenVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_account", token_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Buyer's token account for wallet-funded tickets, or the escrow's
+    /// token account for escrow-funded ones. Verified in handler.
+    #[account(
+        mut,
+        token::mint = payment_mint
+    )]
+    pub refund_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Original agent token escrow, for escrow-funded tickets
+    #[account(
+        mut,
+        constraint = refund_escrow.key() == ticket.funding_account @ TixError::InvalidRefundRecipient
+    )]
+    pub refund_escrow: Option<Box<Account<'info, TokenEscrow>>>,
+
+    /// Escrow refunds only: the agent's and owner's per-event counters
+    #[account(mut)]
+    pub agent_ticket_counter: Option<Box<Account<'info, AgentTicketCounter>>>,
+
+    #[account(mut)]
+    pub user_ticket_counter: Option<Box<Account<'info, UserTicketCounter>>>,
+
+    pub holder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
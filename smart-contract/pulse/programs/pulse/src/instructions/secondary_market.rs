@@ -0,0 +1,962 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{
+    close_account, transfer_checked, CloseAccount, Mint, Token, TokenAccount, TransferChecked,
+};
+
+use crate::state::{
+    AgentDelegate, AgentEscrow, AgentTicketCounter, AIAgent, Event, FundingSource, GlobalConfig,
+    ListingPricing, MarketListing, Offer, Ticket, TicketStatus, TicketTier, UserTicketCounter,
+};
+use crate::error::TixError;
+use crate::math::split_sale;
+use crate::pricing::validate_listing_pricing;
+use crate::constraints::validate_price_cap;
+use crate::instructions::delegate::authorize_agent_spend;
+use crate::instructions::escrow::{check_agent_budget, check_purchase_threshold};
+use crate::instructions::limits::{count_agent_ticket, count_user_ticket};
+use crate::instructions::primary_market::pay_from_wallet;
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// List a ticket for resale at a fixed price. The NFT moves into a vault
+/// owned by the listing until it sells, is cancelled or expires. Resale is
+/// priced in lamports, so only tickets bought in lamports can be listed.
+pub fn list_ticket(ctx: Context<ListTicket>, list_price: u64, duration: i64) -> Result<()> {
+    let bump = ctx.bumps.listing;
+    ctx.accounts.open(list_price, ListingPricing::Fixed, duration, bump)
+}
+
+/// List a ticket as a Dutch auction: the price starts at `start_price` and
+/// drops by `decay_bps` of it every `decay_interval` seconds, down to `min_price`
+pub fn list_ticket_dutch(
+    ctx: Context<ListTicket>,
+    start_price: u64,
+    min_price: u64,
+    decay_bps: u16,
+    decay_interval: i64,
+    duration: i64,
+) -> Result<()> {
+    let pricing = ListingPricing::Dutch { min_price, decay_bps, decay_interval };
+    let bump = ctx.bumps.listing;
+    ctx.accounts.open(start_price, pricing, duration, bump)
+}
+
+/// Seller changes the asking price of an open listing
+pub fn update_listing(ctx: Context<UpdateListing>, list_price: u64) -> Result<()> {
+    let cap_bps = ctx.accounts.event.resale_price_cap_bps(&ctx.accounts.config);
+    let listing = &mut ctx.accounts.listing;
+
+    require!(!listing.is_expired(Clock::get()?.unix_timestamp), TixError::ListingExpired);
+    require!(list_price > 0, TixError::InvalidPrice);
+    validate_listing_pricing(&listing.pricing, list_price)?;
+    validate_price_cap(list_price, ctx.accounts.ticket.original_price, cap_bps)?;
+
+    listing.list_price = list_price;
+
+    msg!("Listing for ticket {} repriced to {} lamports", listing.ticket_mint, list_price);
+    Ok(())
+}
+
+/// Seller withdraws a listing and takes the NFT back
+pub fn cancel_listing(ctx: Context<CloseListing>) -> Result<()> {
+    require!(
+        ctx.accounts.authority.key() == ctx.accounts.listing.seller,
+        TixError::Unauthorized
+    );
+
+    ctx.accounts.return_to_seller()?;
+
+    msg!("Listing for ticket {} cancelled", ctx.accounts.listing.ticket_mint);
+    Ok(())
+}
+
+/// Anyone may close an expired listing; the NFT goes back to the seller
+pub fn reclaim_expired_listing(ctx: Context<CloseListing>) -> Result<()> {
+    require!(
+        ctx.accounts.listing.is_expired(Clock::get()?.unix_timestamp),
+        TixError::ListingNotExpired
+    );
+
+    ctx.accounts.return_to_seller()?;
+
+    msg!("Expired listing for ticket {} closed", ctx.accounts.listing.ticket_mint);
+    Ok(())
+}
+
+/// Buy a listed ticket at its current price. The buyer pays the seller, the
+/// organizer royalty and the protocol fee, and receives the NFT, all in one
+/// instruction. `max_price` guards against the listing being repriced in between.
+pub fn buy_listed_ticket(ctx: Context<BuyListedTicket>, max_price: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let listing = &ctx.accounts.listing;
+    let event = &ctx.accounts.event;
+
+    require!(!listing.is_expired(now), TixError::ListingExpired);
+    require!(ctx.accounts.ticket.status == TicketStatus::Active, TixError::TicketNotActive);
+    require!(!event.is_cancelled, TixError::EventCancelled);
+
+    let price = listing.current_price(now);
+    require!(price <= max_price, TixError::PriceAboveMax);
+    require!(
+        ctx.accounts.tier.waitlist_queued == 0 || price > ctx.accounts.ticket.original_price,
+        TixError::ReservedForWaitlist
+    );
+    // The cap may have been tightened since the ticket was listed
+    validate_price_cap(
+        price,
+        ctx.accounts.ticket.original_price,
+        event.resale_price_cap_bps(&ctx.accounts.config),
+    )?;
+
+    let split = split_sale(price, ctx.accounts.config.protocol_fee_bps, event.royalty_bps)?;
+
+    let buyer_info = ctx.accounts.buyer.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    pay_from_wallet(&system_program, &buyer_info, &ctx.accounts.treasury, split.protocol_fee)?;
+    pay_from_wallet(&system_program, &buyer_info, &ctx.accounts.fee_recipient, split.organizer_fee)?;
+    pay_from_wallet(&system_program, &buyer_info, &ctx.accounts.seller, split.net)?;
+
+    ListingRelease {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        listing: &ctx.accounts.listing,
+        listing_vault: &ctx.accounts.listing_vault.to_account_info(),
+        ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+        seller: &ctx.accounts.seller.to_account_info(),
+    }
+    .release(&ctx.accounts.buyer_token_account.to_account_info())?;
+
+    ctx.accounts.tier.record_resale(price, now);
+
+    // Refunds now follow the new holder
+    let ticket = &mut ctx.accounts.ticket;
+    ticket.funding_source = FundingSource::Wallet;
+    ticket.funding_account = ctx.accounts.buyer.key();
+    ticket.purchase_price = price;
+
+    msg!(
+        "Ticket {} resold for {} lamports (seller {}, royalty {}, protocol fee {})",
+        ticket.mint,
+        price,
+        split.net,
+        split.organizer_fee,
+        split.protocol_fee
+    );
+    Ok(())
+}
+
+/// Agent buys a listed ticket at its current price from its escrow balance,
+/// under the same budget and per-event caps as primary escrow purchases.
+/// The NFT goes to the agent owner and refunds follow the escrow.
+pub fn buy_listed_ticket_with_escrow(
+    ctx: Context<BuyListedTicketWithEscrow>,
+    max_price: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let authority = ctx.accounts.authority.key();
+    let listing = &ctx.accounts.listing;
+    let event = &ctx.accounts.event;
+
+    require!(!listing.is_expired(now), TixError::ListingExpired);
+    require!(ctx.accounts.ticket.status == TicketStatus::Active, TixError::TicketNotActive);
+    require!(!event.is_cancelled, TixError::EventCancelled);
+
+    let price = listing.current_price(now);
+    require!(price <= max_price, TixError::PriceAboveMax);
+    require!(
+        ctx.accounts.tier.waitlist_queued == 0 || price > ctx.accounts.ticket.original_price,
+        TixError::ReservedForWaitlist
+    );
+    validate_price_cap(
+        price,
+        ctx.accounts.ticket.original_price,
+        event.resale_price_cap_bps(&ctx.accounts.config),
+    )?;
+
+    let agent = &ctx.accounts.agent;
+    check_agent_budget(agent, &ctx.accounts.escrow, price)?;
+    let saved = check_purchase_threshold(agent, price, ctx.accounts.tier.reference_price(now)?)?;
+
+    authorize_agent_spend(
+        agent,
+        ctx.accounts.delegate.as_mut(),
+        &authority,
+        AgentDelegate::SCOPE_BUY_RESALE,
+        price,
+        now,
+    )?;
+
+    count_agent_ticket(
+        &mut ctx.accounts.agent_ticket_counter,
+        agent.key(),
+        event.key(),
+        ctx.bumps.agent_ticket_counter,
+        agent.max_tickets_per_event,
+    )?;
+    count_user_ticket(
+        &mut ctx.accounts.user_ticket_counter,
+        agent.owner,
+        event.key(),
+        ctx.bumps.user_ticket_counter,
+        event.max_tickets_per_wallet,
+    )?;
+
+    let split = split_sale(price, ctx.accounts.config.protocol_fee_bps, event.royalty_bps)?;
+
+    **ctx.accounts.escrow.to_account_info().try_borrow_mut_lamports()? -= price;
+    **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += split.protocol_fee;
+    **ctx.accounts.fee_recipient.to_account_info().try_borrow_mut_lamports()? += split.organizer_fee;
+    **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += split.net;
+
+    let escrow = &mut ctx.accounts.escrow;
+    escrow.balance = escrow.balance.checked_sub(price).ok_or(TixError::MathUnderflow)?;
+    escrow.total_spent = escrow.total_spent.checked_add(price).ok_or(TixError::MathOverflow)?;
+    escrow.last_activity = now;
+
+    let agent = &mut ctx.accounts.agent;
+    agent.spent_budget = agent.spent_budget.checked_add(price).ok_or(TixError::MathOverflow)?;
+    agent.tickets_purchased += 1;
+    agent.money_saved = agent.money_saved.checked_add(saved).ok_or(TixError::MathOverflow)?;
+
+    ctx.accounts.tier.record_resale(price, now);
+
+    ListingRelease {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        listing: &ctx.accounts.listing,
+        listing_vault: &ctx.accounts.listing_vault.to_account_info(),
+        ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+        seller: &ctx.accounts.seller.to_account_info(),
+    }
+    .release(&ctx.accounts.owner_token_account.to_account_info())?;
+
+    let ticket = &mut ctx.accounts.ticket;
+    ticket.funding_source = FundingSource::Escrow;
+    ticket.funding_account = ctx.accounts.escrow.key();
+    ticket.purchase_price = price;
+
+    msg!(
+        "Agent {} bought resale ticket {} for {} lamports",
+        ctx.accounts.agent.agent_id,
+        ticket.mint,
+        price
+    );
+    Ok(())
+}
+
+/// Bid on a ticket. The bid is locked in the offer account until the holder
+/// accepts it, the bidder cancels it or it expires.
+pub fn make_offer(ctx: Context<MakeOffer>, amount: u64, duration: i64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = &ctx.accounts.config;
+    let event = &ctx.accounts.event;
+    let ticket = &ctx.accounts.ticket;
+
+    require!(ticket.status == TicketStatus::Active, TixError::TicketNotActive);
+    require!(ticket.payment_mint == Pubkey::default(), TixError::WrongPaymentMint);
+    require!(!event.is_cancelled, TixError::EventCancelled);
+    require!(now < event.event_start_time, TixError::CannotOfferAfterEventStart);
+    require!(amount > 0, TixError::InvalidPrice);
+    require!(
+        duration >= config.min_listing_duration && duration <= config.max_listing_duration,
+        TixError::InvalidListingDuration
+    );
+    validate_price_cap(amount, ticket.original_price, event.resale_price_cap_bps(config))?;
+
+    pay_from_wallet(
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.bidder.to_account_info(),
+        &ctx.accounts.offer.to_account_info(),
+        amount,
+    )?;
+
+    ctx.accounts.offer.set_inner(Offer {
+        bidder: ctx.accounts.bidder.key(),
+        ticket_mint: ticket.mint,
+        event: event.key(),
+        amount,
+        created_at: now,
+        expires_at: now.checked_add(duration).ok_or(TixError::MathOverflow)?,
+        bump: ctx.bumps.offer,
+    });
+
+    msg!("Offer of {} lamports made on ticket {}", amount, ticket.mint);
+    Ok(())
+}
+
+/// Bidder withdraws an offer; the bid and rent go back to them
+pub fn cancel_offer(ctx: Context<CloseOffer>) -> Result<()> {
+    require!(
+        ctx.accounts.authority.key() == ctx.accounts.offer.bidder,
+        TixError::Unauthorized
+    );
+
+    msg!("Offer on ticket {} cancelled", ctx.accounts.offer.ticket_mint);
+    Ok(())
+}
+
+/// Anyone may close an expired offer; the bid and rent go back to the bidder
+pub fn reclaim_expired_offer(ctx: Context<CloseOffer>) -> Result<()> {
+    require!(
+        ctx.accounts.offer.is_expired(Clock::get()?.unix_timestamp),
+        TixError::OfferNotExpired
+    );
+
+    msg!("Expired offer on ticket {} closed", ctx.accounts.offer.ticket_mint);
+    Ok(())
+}
+
+/// Holder accepts an offer: the NFT goes to the bidder and the locked bid is
+/// paid out to the holder, the organizer royalty and the protocol fee.
+pub fn accept_offer(ctx: Context<AcceptOffer>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let offer = &ctx.accounts.offer;
+    let event = &ctx.accounts.event;
+
+    require!(!offer.is_expired(now), TixError::OfferExpired);
+    require!(ctx.accounts.ticket.status == TicketStatus::Active, TixError::TicketNotActive);
+    require!(!event.is_cancelled, TixError::EventCancelled);
+
+    let price = offer.amount;
+    require!(
+        ctx.accounts.tier.waitlist_queued == 0 || price > ctx.accounts.ticket.original_price,
+        TixError::ReservedForWaitlist
+    );
+    validate_price_cap(
+        price,
+        ctx.accounts.ticket.original_price,
+        event.resale_price_cap_bps(&ctx.accounts.config),
+    )?;
+
+    let split = split_sale(price, ctx.accounts.config.protocol_fee_bps, event.royalty_bps)?;
+
+    **ctx.accounts.offer.to_account_info().try_borrow_mut_lamports()? -= price;
+    **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += split.protocol_fee;
+    **ctx.accounts.fee_recipient.to_account_info().try_borrow_mut_lamports()? += split.organizer_fee;
+    **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += split.net;
+
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.seller_token_account.to_account_info(),
+                mint: ctx.accounts.ticket_mint.to_account_info(),
+                to: ctx.accounts.bidder_token_account.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+            },
+        ),
+        1,
+        0,
+    )?;
+
+    ctx.accounts.tier.record_resale(price, now);
+
+    // Refunds now follow the new holder
+    let ticket = &mut ctx.accounts.ticket;
+    ticket.funding_source = FundingSource::Wallet;
+    ticket.funding_account = ctx.accounts.bidder.key();
+    ticket.purchase_price = price;
+
+    msg!(
+        "Offer accepted: ticket {} sold for {} lamports (seller {}, royalty {}, protocol fee {})",
+        ticket.mint,
+        price,
+        split.net,
+        split.organizer_fee,
+        split.protocol_fee
+    );
+    Ok(())
+}
+
+// =====================================
+// HELPERS
+// =====================================
+
+/// Accounts needed to move a listed NFT out of its vault and close the listing vault
+pub struct ListingRelease<'a, 'info> {
+    pub token_program: &'a AccountInfo<'info>,
+    pub listing: &'a Account<'info, MarketListing>,
+    pub listing_vault: &'a AccountInfo<'info>,
+    pub ticket_mint: &'a AccountInfo<'info>,
+    pub seller: &'a AccountInfo<'info>,
+}
+
+impl<'info> ListingRelease<'_, 'info> {
+    /// Send the NFT to `to` and return the vault's rent to the seller
+    pub fn release(&self, to: &AccountInfo<'info>) -> Result<()> {
+        let bump = [self.listing.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"listing", self.listing.ticket_mint.as_ref(), &bump]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                TransferChecked {
+                    from: self.listing_vault.clone(),
+                    mint: self.ticket_mint.clone(),
+                    to: to.clone(),
+                    authority: self.listing.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+            0,
+        )?;
+
+        close_account(CpiContext::new_with_signer(
+            self.token_program.clone(),
+            CloseAccount {
+                account: self.listing_vault.clone(),
+                destination: self.seller.clone(),
+                authority: self.listing.to_account_info(),
+            },
+            signer_seeds,
+        ))
+    }
+}
+
+impl ListTicket<'_> {
+    /// Move the NFT into the listing vault and record the listing
+    fn open(&mut self, list_price: u64, pricing: ListingPricing, duration: i64, bump: u8) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config = &self.config;
+        let event = &self.event;
+        let ticket = &self.ticket;
+
+        require!(ticket.status == TicketStatus::Active, TixError::TicketNotActive);
+        require!(ticket.payment_mint == Pubkey::default(), TixError::WrongPaymentMint);
+        require!(!event.is_cancelled, TixError::EventCancelled);
+        require!(now < event.event_start_time, TixError::CannotListAfterEventStart);
+        require!(list_price > 0, TixError::InvalidPrice);
+        require!(
+            duration >= config.min_listing_duration && duration <= config.max_listing_duration,
+            TixError::InvalidListingDuration
+        );
+        validate_listing_pricing(&pricing, list_price)?;
+        // A Dutch listing never asks more than its start price
+        validate_price_cap(list_price, ticket.original_price, event.resale_price_cap_bps(config))?;
+
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.seller_token_account.to_account_info(),
+                    mint: self.ticket_mint.to_account_info(),
+                    to: self.listing_vault.to_account_info(),
+                    authority: self.seller.to_account_info(),
+                },
+            ),
+            1,
+            0,
+        )?;
+
+        self.listing.set_inner(MarketListing {
+            seller: self.seller.key(),
+            ticket_mint: ticket.mint,
+            event: event.key(),
+            tier: ticket.tier,
+            list_price,
+            pricing,
+            original_price: ticket.original_price,
+            created_at: now,
+            expires_at: now.checked_add(duration).ok_or(TixError::MathOverflow)?,
+            bump,
+        });
+
+        msg!("Ticket {} listed for {} lamports", ticket.mint, list_price);
+        Ok(())
+    }
+}
+
+impl<'info> CloseListing<'info> {
+    fn return_to_seller(&self) -> Result<()> {
+        ListingRelease {
+            token_program: &self.token_program.to_account_info(),
+            listing: &self.listing,
+            listing_vault: &self.listing_vault.to_account_info(),
+            ticket_mint: &self.ticket_mint.to_account_info(),
+            seller: &self.seller.to_account_info(),
+        }
+        .release(&self.seller_token_account.to_account_info())
+    }
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+pub struct ListTicket<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket", ticket_mint.key().as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.event == event.key() @ TixError::InvalidInput
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = ticket.is_held_by(&seller_token_account, &seller.key()) @ TixError::TicketNotHeld
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        seeds = [b"listing", ticket_mint.key().as_ref()],
+        bump,
+        payer = seller,
+        space = MarketListing::SPACE
+    )]
+    pub listing: Box<Account<'info, MarketListing>>,
+
+    #[account(
+        init,
+        seeds = [b"listing_vault", listing.key().as_ref()],
+        bump,
+        payer = seller,
+        token::mint = ticket_mint,
+        token::authority = listing
+    )]
+    pub listing_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateListing<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        constraint = event.key() == listing.event @ TixError::InvalidInput
+    )]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket", listing.ticket_mint.as_ref()],
+        bump = ticket.bump
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", listing.ticket_mint.as_ref()],
+        bump = listing.bump,
+        constraint = listing.seller == seller.key() @ TixError::Unauthorized
+    )]
+    pub listing: Account<'info, MarketListing>,
+
+    pub seller: Signer<'info>,
+}
+
+/// Shared by cancel (seller-signed) and expiry (permissionless)
+#[derive(Accounts)]
+pub struct CloseListing<'info> {
+    #[account(
+        mut,
+        seeds = [b"listing", listing.ticket_mint.as_ref()],
+        bump = listing.bump,
+        close = seller
+    )]
+    pub listing: Box<Account<'info, MarketListing>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing_vault", listing.key().as_ref()],
+        bump
+    )]
+    pub listing_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = ticket_mint.key() == listing.ticket_mint @ TixError::InvalidInput
+    )]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = seller.key() == listing.seller @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against listing, receives the NFT and rent back
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyListedTicket<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        constraint = event.key() == listing.event @ TixError::InvalidInput
+    )]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", listing.ticket_mint.as_ref()],
+        bump = ticket.bump
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        mut,
+        constraint = tier.key() == ticket.tier @ TixError::InvalidInput
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", listing.ticket_mint.as_ref()],
+        bump = listing.bump,
+        close = seller
+    )]
+    pub listing: Box<Account<'info, MarketListing>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing_vault", listing.key().as_ref()],
+        bump
+    )]
+    pub listing_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = ticket_mint.key() == listing.ticket_mint @ TixError::InvalidInput
+    )]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = seller.key() == listing.seller @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against listing
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against config
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = fee_recipient.key() == event.fee_recipient @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against event
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyListedTicketWithEscrow<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        constraint = event.key() == listing.event @ TixError::InvalidInput
+    )]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", listing.ticket_mint.as_ref()],
+        bump = ticket.bump
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        mut,
+        constraint = tier.key() == ticket.tier @ TixError::InvalidInput
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", listing.ticket_mint.as_ref()],
+        bump = listing.bump,
+        close = seller
+    )]
+    pub listing: Box<Account<'info, MarketListing>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing_vault", listing.key().as_ref()],
+        bump
+    )]
+    pub listing_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = ticket_mint.key() == listing.ticket_mint @ TixError::InvalidInput
+    )]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent_owner_wallet.key().as_ref(), agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Box<Account<'info, AIAgent>>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", agent.key().as_ref(), agent_owner_wallet.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Box<Account<'info, AgentEscrow>>,
+
+    /// Required unless the authority is the agent owner
+    #[account(
+        mut,
+        seeds = [b"delegate", agent.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, AgentDelegate>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"agent_ticket_counter", agent.key().as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = AgentTicketCounter::SPACE
+    )]
+    pub agent_ticket_counter: Box<Account<'info, AgentTicketCounter>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"user_ticket_counter", agent_owner_wallet.key().as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = UserTicketCounter::SPACE
+    )]
+    pub user_ticket_counter: Box<Account<'info, UserTicketCounter>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = agent_owner_wallet,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = agent_owner_wallet.key() == agent.owner @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against agent, receives the NFT
+    pub agent_owner_wallet: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = seller.key() == listing.seller @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against listing
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against config
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = fee_recipient.key() == event.fee_recipient @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against event
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MakeOffer<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        constraint = event.key() == ticket.event @ TixError::InvalidInput
+    )]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        seeds = [b"ticket", ticket.mint.as_ref()],
+        bump = ticket.bump
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        init,
+        seeds = [b"offer", ticket.mint.as_ref(), bidder.key().as_ref()],
+        bump,
+        payer = bidder,
+        space = Offer::SPACE
+    )]
+    pub offer: Box<Account<'info, Offer>>,
+
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Shared by cancel (bidder-signed) and expiry (permissionless)
+#[derive(Accounts)]
+pub struct CloseOffer<'info> {
+    #[account(
+        mut,
+        seeds = [b"offer", offer.ticket_mint.as_ref(), offer.bidder.as_ref()],
+        bump = offer.bump,
+        close = bidder
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        constraint = bidder.key() == offer.bidder @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against offer, receives the bid and rent back
+    pub bidder: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOffer<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        constraint = event.key() == offer.event @ TixError::InvalidInput
+    )]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", offer.ticket_mint.as_ref()],
+        bump = ticket.bump
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        mut,
+        constraint = tier.key() == ticket.tier @ TixError::InvalidInput
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", offer.ticket_mint.as_ref(), offer.bidder.as_ref()],
+        bump = offer.bump,
+        close = bidder
+    )]
+    pub offer: Box<Account<'info, Offer>>,
+
+    #[account(
+        constraint = ticket_mint.key() == offer.ticket_mint @ TixError::InvalidInput
+    )]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = ticket.is_held_by(&seller_token_account, &seller.key()) @ TixError::TicketNotHeld
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = bidder,
+    )]
+    pub bidder_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = bidder.key() == offer.bidder @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against offer, receives the NFT and the offer rent back
+    pub bidder: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against config
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = fee_recipient.key() == event.fee_recipient @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against event
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
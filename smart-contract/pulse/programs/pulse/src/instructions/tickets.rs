@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Event, Ticket, TicketStatus};
+use crate::error::TixError;
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// Organizer sets or clears the seat printed on an active ticket
+pub fn assign_seat(ctx: Context<ManageTicket>, seat_info: Option<String>) -> Result<()> {
+    let ticket = &mut ctx.accounts.ticket;
+
+    require!(ticket.status == TicketStatus::Active, TixError::TicketNotActive);
+    if let Some(seat) = &seat_info {
+        require!(seat.len() <= Ticket::MAX_SEAT_INFO_LENGTH, TixError::SeatInfoTooLong);
+    }
+
+    ticket.seat_info = seat_info;
+
+    msg!("Seat updated for ticket {}", ticket.mint);
+    Ok(())
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+pub struct ManageTicket<'info> {
+    #[account(
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket.mint.as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.event == event.key() @ TixError::InvalidInput
+    )]
+    pub ticket: Account<'info, Ticket>,
+
+    #[account(
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+}
@@ -0,0 +1,496 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::state::{
+    AgentTicketCounter, AIAgent, Event, EventTokenVault, FundingSource,
+    GlobalConfig, Ticket, TicketStatus, TicketTier, TokenEscrow, UserTicketCounter,
+};
+use crate::error::TixError;
+use crate::math::split_sale;
+use crate::instructions::escrow::check_purchase_threshold;
+use crate::instructions::limits::{count_agent_ticket, count_user_ticket};
+use crate::instructions::primary_market::{check_primary_sale, record_token_sale, TokenSalePayment};
+use crate::instructions::nft::{mint_ticket, ticket_name, TicketMintAccounts, TICKET_SYMBOL};
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// Create an SPL-token escrow for an agent, one per payment mint
+pub fn create_token_escrow(ctx: Context<CreateTokenEscrow>, max_price_per_ticket: u64) -> Result<()> {
+    let escrow = &mut ctx.accounts.token_escrow;
+    let clock = Clock::get()?;
+
+    escrow.agent = ctx.accounts.agent.key();
+    escrow.owner = ctx.accounts.owner.key();
+    escrow.mint = ctx.accounts.mint.key();
+    escrow.balance = 0;
+    escrow.total_deposited = 0;
+    escrow.total_withdrawn = 0;
+    escrow.total_spent = 0;
+    escrow.max_price_per_ticket = max_price_per_ticket;
+    escrow.created_at = clock.unix_timestamp;
+    escrow.last_activity = clock.unix_timestamp;
+    escrow.bump = ctx.bumps.token_escrow;
+
+    msg!(
+        "Token escrow for mint {} created for agent {}",
+        escrow.mint,
+        ctx.accounts.agent.agent_id
+    );
+    Ok(())
+}
+
+/// Deposit tokens from the owner into the agent's token escrow
+pub fn deposit_to_token_escrow(ctx: Context<DepositToTokenEscrow>, amount: u64) -> Result<()> {
+    require!(amount > 0, TixError::InvalidBudget);
+
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    let escrow = &mut ctx.accounts.token_escrow;
+    escrow.balance = escrow.balance.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    escrow.total_deposited = escrow.total_deposited.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    escrow.last_activity = Clock::get()?.unix_timestamp;
+
+    msg!("Deposited {} of mint {} to token escrow", amount, escrow.mint);
+    Ok(())
+}
+
+/// Withdraw unused tokens from the agent's token escrow back to the owner
+pub fn withdraw_from_token_escrow(ctx: Context<WithdrawFromTokenEscrow>, amount: u64) -> Result<()> {
+    require!(amount > 0, TixError::InvalidBudget);
+    require!(ctx.accounts.token_escrow.balance >= amount, TixError::InsufficientEscrowBalance);
+
+    let escrow = &ctx.accounts.token_escrow;
+    let bump = [escrow.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"token_escrow",
+        escrow.agent.as_ref(),
+        escrow.owner.as_ref(),
+        escrow.mint.as_ref(),
+        &bump,
+    ]];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.token_escrow.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    let escrow = &mut ctx.accounts.token_escrow;
+    escrow.balance = escrow.balance.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    escrow.total_withdrawn = escrow.total_withdrawn.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    escrow.last_activity = Clock::get()?.unix_timestamp;
+
+    msg!("Withdrew {} of mint {} from token escrow", amount, escrow.mint);
+    Ok(())
+}
+
+/// Buy a token-priced ticket from the agent's token escrow and mint the NFT
+/// to the agent owner. Only the owner may sign: delegate spend caps are kept
+/// in lamports and cannot bound a token spend, and the agent's lamport budget
+/// does not apply either; the escrow's `max_price_per_ticket` does.
+pub fn buy_and_mint_with_token_escrow(
+    ctx: Context<BuyAndMintWithTokenEscrow>,
+    _tier_id: String,
+    _agent_owner: Pubkey,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let agent = &ctx.accounts.agent;
+    let escrow = &ctx.accounts.token_escrow;
+
+    let price = check_primary_sale(&ctx.accounts.event, &ctx.accounts.tier, now)?;
+
+    require!(agent.is_active, TixError::AgentInactive);
+    require!(agent.auto_purchase_enabled, TixError::AutoPurchaseDisabled);
+    require!(escrow.balance >= price, TixError::InsufficientEscrowBalance);
+    require!(
+        escrow.max_price_per_ticket == 0 || price <= escrow.max_price_per_ticket,
+        TixError::InsufficientAgentBudget
+    );
+    // money_saved is kept in lamports, so only the threshold applies here
+    check_purchase_threshold(agent, price, ctx.accounts.tier.reference_price(now)?)?;
+
+    count_agent_ticket(
+        &mut ctx.accounts.agent_ticket_counter,
+        agent.key(),
+        ctx.accounts.event.key(),
+        ctx.bumps.agent_ticket_counter,
+        agent.max_tickets_per_event,
+    )?;
+    count_user_ticket(
+        &mut ctx.accounts.user_ticket_counter,
+        agent.owner,
+        ctx.accounts.event.key(),
+        ctx.bumps.user_ticket_counter,
+        ctx.accounts.event.max_tickets_per_wallet,
+    )?;
+
+    let split = split_sale(
+        price,
+        ctx.accounts.config.protocol_fee_bps,
+        ctx.accounts.event.organizer_fee_bps,
+    )?;
+
+    let bump = [escrow.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"token_escrow",
+        escrow.agent.as_ref(),
+        escrow.owner.as_ref(),
+        escrow.mint.as_ref(),
+        &bump,
+    ]];
+
+    TokenSalePayment {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        mint: &ctx.accounts.payment_mint,
+        from: &ctx.accounts.escrow_token_account.to_account_info(),
+        authority: &ctx.accounts.token_escrow.to_account_info(),
+        treasury: &ctx.accounts.treasury_token_account.to_account_info(),
+        vault: &ctx.accounts.vault_token_account.to_account_info(),
+    }
+    .pay(&split, signer_seeds)?;
+
+    let escrow = &mut ctx.accounts.token_escrow;
+    escrow.balance = escrow.balance.checked_sub(price).ok_or(TixError::MathUnderflow)?;
+    escrow.total_spent = escrow.total_spent.checked_add(price).ok_or(TixError::MathOverflow)?;
+    escrow.last_activity = now;
+
+    let agent = &mut ctx.accounts.agent;
+    agent.tickets_purchased += 1;
+
+    record_token_sale(
+        &mut ctx.accounts.event,
+        &mut ctx.accounts.tier,
+        &mut ctx.accounts.token_vault,
+        price,
+        &split,
+    )?;
+
+    let event = &ctx.accounts.event;
+    let tier = &ctx.accounts.tier;
+
+    ctx.accounts.ticket.set_inner(Ticket {
+        mint: ctx.accounts.ticket_mint.key(),
+        event: event.key(),
+        tier: tier.key(),
+        original_price: price,
+        payment_mint: ctx.accounts.payment_mint.key(),
+        funding_source: FundingSource::Escrow,
+        funding_account: ctx.accounts.token_escrow.key(),
+        purchase_price: price,
+        refund_amount: split.held(),
+        organizer_fee: split.organizer_fee,
+        status: TicketStatus::Active,
+        purchased_at: now,
+        validated_at: None,
+        seat_info: None,
+        entry_count: 0,
+        is_inside: false,
+        last_entry_at: 0,
+        last_exit_at: 0,
+        last_checkin_nonce: 0,
+        bump: ctx.bumps.ticket,
+    });
+
+    mint_ticket(
+        TicketMintAccounts {
+            event: &event.to_account_info(),
+            ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+            metadata: &ctx.accounts.metadata.to_account_info(),
+            recipient_token_account: &ctx.accounts.owner_token_account.to_account_info(),
+            payer: &ctx.accounts.authority.to_account_info(),
+            token_program: &ctx.accounts.token_program.to_account_info(),
+            token_metadata_program: &ctx.accounts.token_metadata_program.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+            rent: &ctx.accounts.rent.to_account_info(),
+        },
+        event,
+        ticket_name(event, tier),
+        TICKET_SYMBOL.to_string(),
+        event.metadata_uri.clone(),
+    )?;
+
+    msg!(
+        "Agent {} bought and minted ticket {} for {} of mint {}",
+        ctx.accounts.agent.agent_id,
+        ctx.accounts.ticket_mint.key(),
+        price,
+        ctx.accounts.payment_mint.key()
+    );
+    Ok(())
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+pub struct CreateTokenEscrow<'info> {
+    #[account(
+        seeds = [b"agent", owner.key().as_ref(), agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AIAgent>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        seeds = [b"token_escrow", agent.key().as_ref(), owner.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = owner,
+        space = TokenEscrow::SPACE
+    )]
+    pub token_escrow: Account<'info, TokenEscrow>,
+
+    #[account(
+        init,
+        seeds = [b"token_escrow_account", token_escrow.key().as_ref()],
+        bump,
+        payer = owner,
+        token::mint = mint,
+        token::authority = token_escrow
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner.key() == agent.owner @ TixError::Unauthorized
+    )]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToTokenEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_escrow", agent.key().as_ref(), owner.key().as_ref(), mint.key().as_ref()],
+        bump = token_escrow.bump
+    )]
+    pub token_escrow: Account<'info, TokenEscrow>,
+
+    #[account(
+        mut,
+        seeds = [b"token_escrow_account", token_escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = agent.owner == owner.key() @ TixError::Unauthorized
+    )]
+    pub agent: Account<'info, AIAgent>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = owner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromTokenEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_escrow", agent.key().as_ref(), owner.key().as_ref(), mint.key().as_ref()],
+        bump = token_escrow.bump
+    )]
+    pub token_escrow: Account<'info, TokenEscrow>,
+
+    #[account(
+        mut,
+        seeds = [b"token_escrow_account", token_escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = agent.owner == owner.key() @ TixError::Unauthorized
+    )]
+    pub agent: Account<'info, AIAgent>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = owner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier_id: String, agent_owner: Pubkey)]
+pub struct BuyAndMintWithTokenEscrow<'info> {
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"tier", event.key().as_ref(), tier_id.as_bytes()],
+        bump = tier.bump,
+        constraint = !tier.is_sol_priced() @ TixError::WrongPaymentMint
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent_owner.as_ref(), agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Box<Account<'info, AIAgent>>,
+
+    #[account(
+        constraint = payment_mint.key() == tier.payment_mint @ TixError::WrongPaymentMint
+    )]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"token_escrow",
+            agent.key().as_ref(),
+            agent_owner.as_ref(),
+            payment_mint.key().as_ref()
+        ],
+        bump = token_escrow.bump
+    )]
+    pub token_escrow: Box<Account<'info, TokenEscrow>>,
+
+    #[account(
+        mut,
+        seeds = [b"token_escrow_account", token_escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", event.key().as_ref(), payment_mint.key().as_ref()],
+        bump = token_vault.bump
+    )]
+    pub token_vault: Box<Account<'info, EventTokenVault>>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_account", token_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = config.treasury
+    )]
+    pub treasury_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"agent_ticket_counter", agent.key().as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = AgentTicketCounter::SPACE
+    )]
+    pub agent_ticket_counter: Box<Account<'info, AgentTicketCounter>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"user_ticket_counter", agent_owner.as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = UserTicketCounter::SPACE
+    )]
+    pub user_ticket_counter: Box<Account<'info, UserTicketCounter>>,
+
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 0,
+        mint::authority = event,
+    )]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Metadata account will be created via CPI
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = agent_owner_wallet,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        seeds = [b"ticket", ticket_mint.key().as_ref()],
+        bump,
+        payer = authority,
+        space = Ticket::SPACE
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        constraint = agent_owner_wallet.key() == agent.owner @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against agent, receives the NFT
+    pub agent_owner_wallet: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == agent.owner @ TixError::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
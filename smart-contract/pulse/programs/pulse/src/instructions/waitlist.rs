@@ -0,0 +1,1244 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::state::{
+    AgentDelegate, AgentEscrow, AgentTicketCounter, AIAgent, Event, EventVault, FundingSource, GlobalConfig,
+    MarketListing, Ticket, TicketStatus, TicketTier, UserTicketCounter, Waitlist, WaitlistEntry,
+};
+use crate::error::TixError;
+use crate::math::split_sale;
+use crate::constraints::check_sale_window;
+use crate::pricing::effective_price;
+use crate::instructions::delegate::{authorize_agent_spend, credit_agent_spend};
+use crate::instructions::escrow::{check_agent_budget, check_purchase_threshold};
+use crate::instructions::limits::{count_agent_ticket, count_user_ticket};
+use crate::instructions::primary_market::{pay_from_wallet, record_primary_sale};
+use crate::instructions::secondary_market::ListingRelease;
+use crate::instructions::nft::{mint_ticket, ticket_name, TicketMintAccounts, TICKET_SYMBOL};
+
+// =====================================
+// INSTRUCTIONS
+// =====================================
+
+/// Organizer opens a waitlist on a lamport-priced tier. With a `claim_window`
+/// the head must claim a free seat itself within that many seconds;
+/// with 0 anyone may crank the head's purchase as soon as a seat frees up.
+pub fn open_waitlist(ctx: Context<OpenWaitlist>, claim_window: i64) -> Result<()> {
+    let tier = &ctx.accounts.tier;
+
+    require!(tier.is_sol_priced(), TixError::WrongPaymentMint);
+    require!(!tier.is_cancelled, TixError::TierAlreadyCancelled);
+    require!(claim_window >= 0, TixError::InvalidInput);
+
+    ctx.accounts.waitlist.set_inner(Waitlist {
+        event: ctx.accounts.event.key(),
+        tier: tier.key(),
+        head: 0,
+        tail: 0,
+        claim_window,
+        head_offered_at: 0,
+        bump: ctx.bumps.waitlist,
+    });
+
+    msg!("Waitlist opened for tier {}", tier.tier_id);
+    Ok(())
+}
+
+/// Join a sold-out tier's waitlist from a wallet. `max_price` is locked in
+/// the entry as the deposit; whatever is not spent is returned.
+pub fn join_waitlist(ctx: Context<JoinWaitlist>, max_price: u64) -> Result<()> {
+    check_can_join(&ctx.accounts.event, &ctx.accounts.tier, max_price)?;
+
+    pay_from_wallet(
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.wallet.to_account_info(),
+        &ctx.accounts.entry.to_account_info(),
+        max_price,
+    )?;
+
+    let wallet = ctx.accounts.wallet.key();
+    enqueue(
+        &mut ctx.accounts.waitlist,
+        &mut ctx.accounts.tier,
+        &mut ctx.accounts.entry,
+        WaitlistEntry {
+            waitlist: Pubkey::default(),
+            position: 0,
+            owner: wallet,
+            claimer: wallet,
+            max_price,
+            deposit: max_price,
+            funding_source: FundingSource::Wallet,
+            funding_account: wallet,
+            is_active: true,
+            joined_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.entry,
+        },
+    )
+}
+
+/// Agent joins a sold-out tier's waitlist. The deposit comes out of its
+/// escrow and counts against its budget until it is spent or returned.
+pub fn join_waitlist_with_escrow(ctx: Context<JoinWaitlistWithEscrow>, max_price: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let authority = ctx.accounts.authority.key();
+
+    check_can_join(&ctx.accounts.event, &ctx.accounts.tier, max_price)?;
+    check_agent_budget(&ctx.accounts.agent, &ctx.accounts.escrow, max_price)?;
+    authorize_agent_spend(
+        &ctx.accounts.agent,
+        ctx.accounts.delegate.as_mut(),
+        &authority,
+        AgentDelegate::SCOPE_BUY_PRIMARY,
+        max_price,
+        now,
+    )?;
+
+    **ctx.accounts.escrow.to_account_info().try_borrow_mut_lamports()? -= max_price;
+    **ctx.accounts.entry.to_account_info().try_borrow_mut_lamports()? += max_price;
+
+    let escrow = &mut ctx.accounts.escrow;
+    escrow.balance = escrow.balance.checked_sub(max_price).ok_or(TixError::MathUnderflow)?;
+    escrow.total_spent = escrow.total_spent.checked_add(max_price).ok_or(TixError::MathOverflow)?;
+    escrow.last_activity = now;
+
+    let agent = &mut ctx.accounts.agent;
+    agent.spent_budget = agent.spent_budget.checked_add(max_price).ok_or(TixError::MathOverflow)?;
+
+    enqueue(
+        &mut ctx.accounts.waitlist,
+        &mut ctx.accounts.tier,
+        &mut ctx.accounts.entry,
+        WaitlistEntry {
+            waitlist: Pubkey::default(),
+            position: 0,
+            owner: agent.owner,
+            claimer: authority,
+            max_price,
+            deposit: max_price,
+            funding_source: FundingSource::Escrow,
+            funding_account: escrow.key(),
+            is_active: true,
+            joined_at: now,
+            bump: ctx.bumps.entry,
+        },
+    )
+}
+
+/// Leave the waitlist and take the deposit back. The entry keeps its place
+/// until the head reaches it and `skip_waitlist_entry` closes it.
+pub fn leave_waitlist(ctx: Context<LeaveWaitlist>) -> Result<()> {
+    let entry = &ctx.accounts.entry;
+    let authority = ctx.accounts.authority.key();
+
+    require!(
+        authority == entry.owner || authority == entry.claimer,
+        TixError::Unauthorized
+    );
+    require!(entry.is_active, TixError::WaitlistEntryInactive);
+
+    let deposit = entry.deposit;
+    return_deposit(
+        &mut ctx.accounts.entry,
+        deposit,
+        &ctx.accounts.owner.to_account_info(),
+        ctx.accounts.escrow.as_mut(),
+        ctx.accounts.agent.as_mut(),
+        ctx.accounts.delegate.as_deref(),
+    )?;
+    ctx.accounts.entry.is_active = false;
+
+    let tier = &mut ctx.accounts.tier;
+    tier.waitlist_queued = tier.waitlist_queued.checked_sub(1).ok_or(TixError::MathUnderflow)?;
+
+    msg!("Left waitlist for tier {} at position {}", tier.tier_id, ctx.accounts.entry.position);
+    Ok(())
+}
+
+/// Start the head's claim window once a seat is free. Permissionless.
+pub fn offer_waitlist_seat(ctx: Context<OfferWaitlistSeat>) -> Result<()> {
+    let waitlist = &mut ctx.accounts.waitlist;
+
+    require!(waitlist.claim_window > 0, TixError::InvalidInput);
+    require!(waitlist.head_offered_at == 0, TixError::InvalidInput);
+    require!(ctx.accounts.entry.is_active, TixError::WaitlistEntryInactive);
+    require!(ctx.accounts.tier.seats_left() > 0, TixError::TierSoldOut);
+
+    waitlist.head_offered_at = Clock::get()?.unix_timestamp;
+
+    msg!("Seat offered to waitlist position {}", waitlist.head);
+    Ok(())
+}
+
+/// Close the head entry when it can no longer be served: it was left, its
+/// claim window lapsed, it cannot afford a free seat, or its agent stopped
+/// buying. Permissionless.
+pub fn skip_waitlist_entry(ctx: Context<SkipWaitlistEntry>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let tier = &ctx.accounts.tier;
+    let entry = &ctx.accounts.entry;
+
+    let seat_price = if tier.seats_left() > 0 { Some(effective_price(tier, now)?) } else { None };
+    require!(
+        is_skippable(
+            entry,
+            ctx.accounts.agent.as_deref(),
+            ctx.accounts.waitlist.head_offer_lapsed(now),
+            seat_price,
+        ),
+        TixError::WaitlistHeadNotSkippable
+    );
+
+    if entry.is_active {
+        let deposit = entry.deposit;
+        return_deposit(
+            &mut ctx.accounts.entry,
+            deposit,
+            &ctx.accounts.owner.to_account_info(),
+            ctx.accounts.escrow.as_mut(),
+            ctx.accounts.agent.as_mut(),
+            ctx.accounts.delegate.as_deref(),
+        )?;
+        let tier = &mut ctx.accounts.tier;
+        tier.waitlist_queued = tier.waitlist_queued.checked_sub(1).ok_or(TixError::MathUnderflow)?;
+    }
+
+    advance_head(&mut ctx.accounts.waitlist);
+
+    msg!("Skipped waitlist position {}", ctx.accounts.entry.position);
+    Ok(())
+}
+
+/// Sell a free seat to the head of the waitlist at the tier's current price,
+/// paid from its deposit. The NFT is minted to the entry owner.
+pub fn claim_waitlist_ticket(ctx: Context<ClaimWaitlistTicket>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let entry = &ctx.accounts.entry;
+
+    require!(entry.is_active, TixError::WaitlistEntryInactive);
+    check_claim_authority(&ctx.accounts.waitlist, entry, &ctx.accounts.authority.key(), now)?;
+
+    let price = check_waitlist_sale(&ctx.accounts.event, &ctx.accounts.tier, now)?;
+    require!(price <= entry.max_price, TixError::PriceAboveMax);
+
+    count_user_ticket(
+        &mut ctx.accounts.user_ticket_counter,
+        entry.owner,
+        ctx.accounts.event.key(),
+        ctx.bumps.user_ticket_counter,
+        ctx.accounts.event.max_tickets_per_wallet,
+    )?;
+    let saved = check_escrow_entry(
+        entry,
+        ctx.accounts.agent.as_ref(),
+        ctx.accounts.agent_ticket_counter.as_deref_mut().zip(ctx.bumps.agent_ticket_counter),
+        ctx.accounts.event.key(),
+        price,
+        ctx.accounts.tier.reference_price(now)?,
+    )?;
+
+    let split = split_sale(
+        price,
+        ctx.accounts.config.protocol_fee_bps,
+        ctx.accounts.event.organizer_fee_bps,
+    )?;
+
+    **ctx.accounts.entry.to_account_info().try_borrow_mut_lamports()? -= price;
+    **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += split.protocol_fee;
+    **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? += split.held();
+
+    record_primary_sale(
+        &mut ctx.accounts.event,
+        &mut ctx.accounts.tier,
+        &mut ctx.accounts.vault,
+        price,
+        &split,
+    )?;
+
+    settle_filled_entry(
+        &mut ctx.accounts.waitlist,
+        &mut ctx.accounts.tier,
+        &mut ctx.accounts.entry,
+        price,
+        saved,
+        &ctx.accounts.owner.to_account_info(),
+        ctx.accounts.escrow.as_mut(),
+        ctx.accounts.agent.as_mut(),
+        ctx.accounts.delegate.as_deref(),
+    )?;
+
+    let event = &ctx.accounts.event;
+    let tier = &ctx.accounts.tier;
+    let entry = &ctx.accounts.entry;
+
+    ctx.accounts.ticket.set_inner(Ticket {
+        mint: ctx.accounts.ticket_mint.key(),
+        event: event.key(),
+        tier: tier.key(),
+        original_price: price,
+        payment_mint: Pubkey::default(),
+        funding_source: entry.funding_source,
+        funding_account: entry.funding_account,
+        purchase_price: price,
+        refund_amount: split.held(),
+        organizer_fee: split.organizer_fee,
+        status: TicketStatus::Active,
+        purchased_at: now,
+        validated_at: None,
+        seat_info: None,
+        entry_count: 0,
+        is_inside: false,
+        last_entry_at: 0,
+        last_exit_at: 0,
+        last_checkin_nonce: 0,
+        bump: ctx.bumps.ticket,
+    });
+
+    mint_ticket(
+        TicketMintAccounts {
+            event: &event.to_account_info(),
+            ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+            metadata: &ctx.accounts.metadata.to_account_info(),
+            recipient_token_account: &ctx.accounts.owner_token_account.to_account_info(),
+            payer: &ctx.accounts.authority.to_account_info(),
+            token_program: &ctx.accounts.token_program.to_account_info(),
+            token_metadata_program: &ctx.accounts.token_metadata_program.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+            rent: &ctx.accounts.rent.to_account_info(),
+        },
+        event,
+        ticket_name(event, tier),
+        TICKET_SYMBOL.to_string(),
+        event.metadata_uri.clone(),
+    )?;
+
+    msg!(
+        "Waitlist position {} bought ticket {} for {} lamports",
+        entry.position,
+        ctx.accounts.ticket_mint.key(),
+        price
+    );
+    Ok(())
+}
+
+/// Fill a face-value resale listing for the head of the waitlist, paid from
+/// its deposit. Such listings are held for the waitlist, so anyone may crank
+/// this. Waitlists with a claim window only take seats the head claims itself.
+pub fn fill_waitlist_from_listing(ctx: Context<FillWaitlistFromListing>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let entry = &ctx.accounts.entry;
+    let event = &ctx.accounts.event;
+
+    require!(entry.is_active, TixError::WaitlistEntryInactive);
+    require!(ctx.accounts.waitlist.claim_window == 0, TixError::WaitlistClaimRequired);
+    require!(!ctx.accounts.listing.is_expired(now), TixError::ListingExpired);
+    require!(ctx.accounts.ticket.status == TicketStatus::Active, TixError::TicketNotActive);
+    require!(!event.is_cancelled, TixError::EventCancelled);
+
+    let price = ctx.accounts.listing.current_price(now);
+    require!(price <= ctx.accounts.ticket.original_price, TixError::InvalidPrice);
+    require!(price <= entry.max_price, TixError::PriceAboveMax);
+
+    count_user_ticket(
+        &mut ctx.accounts.user_ticket_counter,
+        entry.owner,
+        event.key(),
+        ctx.bumps.user_ticket_counter,
+        event.max_tickets_per_wallet,
+    )?;
+    let saved = check_escrow_entry(
+        entry,
+        ctx.accounts.agent.as_ref(),
+        ctx.accounts.agent_ticket_counter.as_deref_mut().zip(ctx.bumps.agent_ticket_counter),
+        event.key(),
+        price,
+        ctx.accounts.tier.reference_price(now)?,
+    )?;
+
+    let split = split_sale(price, ctx.accounts.config.protocol_fee_bps, event.royalty_bps)?;
+
+    **ctx.accounts.entry.to_account_info().try_borrow_mut_lamports()? -= price;
+    **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? += split.protocol_fee;
+    **ctx.accounts.fee_recipient.to_account_info().try_borrow_mut_lamports()? += split.organizer_fee;
+    **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += split.net;
+
+    ListingRelease {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        listing: &ctx.accounts.listing,
+        listing_vault: &ctx.accounts.listing_vault.to_account_info(),
+        ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+        seller: &ctx.accounts.seller.to_account_info(),
+    }
+    .release(&ctx.accounts.owner_token_account.to_account_info())?;
+
+    ctx.accounts.tier.record_resale(price, now);
+
+    settle_filled_entry(
+        &mut ctx.accounts.waitlist,
+        &mut ctx.accounts.tier,
+        &mut ctx.accounts.entry,
+        price,
+        saved,
+        &ctx.accounts.owner.to_account_info(),
+        ctx.accounts.escrow.as_mut(),
+        ctx.accounts.agent.as_mut(),
+        ctx.accounts.delegate.as_deref(),
+    )?;
+
+    // Refunds now follow the waitlist entrant's funds
+    let ticket = &mut ctx.accounts.ticket;
+    ticket.funding_source = ctx.accounts.entry.funding_source;
+    ticket.funding_account = ctx.accounts.entry.funding_account;
+    ticket.purchase_price = price;
+
+    msg!(
+        "Waitlist position {} bought resale ticket {} for {} lamports",
+        ctx.accounts.entry.position,
+        ticket.mint,
+        price
+    );
+    Ok(())
+}
+
+// =====================================
+// HELPERS
+// =====================================
+
+/// Joining only makes sense once nothing is left for public sale
+fn check_can_join(event: &Event, tier: &TicketTier, max_price: u64) -> Result<()> {
+    require!(!event.is_cancelled, TixError::EventCancelled);
+    require!(!tier.is_cancelled, TixError::TierAlreadyCancelled);
+    require!(max_price > 0, TixError::InvalidPrice);
+    require!(tier.seats_left() <= tier.waitlist_queued, TixError::TierNotSoldOut);
+    Ok(())
+}
+
+/// Append `entry` at the tail of the queue
+fn enqueue(
+    waitlist: &mut Account<Waitlist>,
+    tier: &mut Account<TicketTier>,
+    entry: &mut Account<WaitlistEntry>,
+    mut fields: WaitlistEntry,
+) -> Result<()> {
+    fields.waitlist = waitlist.key();
+    fields.position = waitlist.tail;
+    entry.set_inner(fields);
+
+    waitlist.tail = waitlist.tail.checked_add(1).ok_or(TixError::MathOverflow)?;
+    tier.waitlist_queued = tier.waitlist_queued.checked_add(1).ok_or(TixError::MathOverflow)?;
+
+    msg!("Joined waitlist for tier {} at position {}", tier.tier_id, entry.position);
+    Ok(())
+}
+
+/// Like `check_primary_sale`, but the free seat is the waitlist's to take
+fn check_waitlist_sale(event: &Event, tier: &TicketTier, now: i64) -> Result<u64> {
+    require!(event.is_active, TixError::EventNotActive);
+    require!(tier.is_active, TixError::TierNotActive);
+    check_sale_window(event, tier, now)?;
+    require!(tier.seats_left() > 0, TixError::TierSoldOut);
+
+    effective_price(tier, now)
+}
+
+/// With a claim window only the head's claimer may buy, and only while the
+/// window is open; without one anyone may crank the purchase
+fn check_claim_authority(waitlist: &Waitlist, entry: &WaitlistEntry, authority: &Pubkey, now: i64) -> Result<()> {
+    if waitlist.claim_window == 0 {
+        return Ok(());
+    }
+
+    require!(*authority == entry.claimer, TixError::Unauthorized);
+    require!(
+        waitlist.head_offered_at != 0 && !waitlist.head_offer_lapsed(now),
+        TixError::ClaimWindowClosed
+    );
+    Ok(())
+}
+
+/// Whether the head can be closed without being served. An escrow entry
+/// whose agent has stopped buying would otherwise hold up the queue.
+fn is_skippable(entry: &WaitlistEntry, agent: Option<&AIAgent>, offer_lapsed: bool, seat_price: Option<u64>) -> bool {
+    let priced_out = seat_price.is_some_and(|price| price > entry.max_price);
+    let agent_stopped = entry.funding_source == FundingSource::Escrow
+        && agent.is_some_and(|agent| !agent.is_active || !agent.auto_purchase_enabled);
+
+    !entry.is_active || offer_lapsed || priced_out || agent_stopped
+}
+
+/// Escrow entries buy under the agent's rules as they stand at fill time,
+/// like any other escrow purchase; the budget was reserved when joining.
+/// Returns how far under the reference price the purchase is.
+fn check_escrow_entry(
+    entry: &WaitlistEntry,
+    agent: Option<&Account<AIAgent>>,
+    agent_counter: Option<(&mut Account<AgentTicketCounter>, u8)>,
+    event: Pubkey,
+    price: u64,
+    reference_price: u64,
+) -> Result<u64> {
+    if entry.funding_source != FundingSource::Escrow {
+        return Ok(0);
+    }
+
+    let agent = agent.ok_or(TixError::InvalidRefundRecipient)?;
+    let (agent_counter, agent_counter_bump) = agent_counter.ok_or(TixError::InvalidRefundRecipient)?;
+    require!(agent.is_active, TixError::AgentInactive);
+    require!(agent.auto_purchase_enabled, TixError::AutoPurchaseDisabled);
+
+    let saved = check_purchase_threshold(agent, price, reference_price)?;
+    count_agent_ticket(
+        agent_counter,
+        agent.key(),
+        event,
+        agent_counter_bump,
+        agent.max_tickets_per_event,
+    )?;
+    Ok(saved)
+}
+
+fn advance_head(waitlist: &mut Waitlist) {
+    waitlist.head += 1;
+    waitlist.head_offered_at = 0;
+}
+
+/// Take `price` out of the entry's deposit; returns what is left to give back
+fn spend_deposit(entry: &mut WaitlistEntry, price: u64) -> Result<u64> {
+    entry.deposit = entry.deposit.checked_sub(price).ok_or(TixError::MathUnderflow)?;
+    Ok(entry.deposit)
+}
+
+/// Give an agent back the escrow balance and budget a deposit reserved
+fn restore_escrow_deposit(escrow: &mut AgentEscrow, agent: &mut AIAgent, amount: u64) -> Result<()> {
+    escrow.balance = escrow.balance.checked_add(amount).ok_or(TixError::MathOverflow)?;
+    escrow.total_spent = escrow.total_spent.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    agent.spent_budget = agent.spent_budget.checked_sub(amount).ok_or(TixError::MathUnderflow)?;
+    Ok(())
+}
+
+/// Spend `price` of the head's deposit, return the rest and move the queue on
+#[allow(clippy::too_many_arguments)]
+fn settle_filled_entry<'info>(
+    waitlist: &mut Account<'info, Waitlist>,
+    tier: &mut Account<'info, TicketTier>,
+    entry: &mut Account<'info, WaitlistEntry>,
+    price: u64,
+    saved: u64,
+    owner: &AccountInfo<'info>,
+    escrow: Option<&mut Account<'info, AgentEscrow>>,
+    mut agent: Option<&mut Account<'info, AIAgent>>,
+    delegate: Option<&AccountInfo<'info>>,
+) -> Result<()> {
+    let remaining = spend_deposit(entry, price)?;
+    if entry.funding_source == FundingSource::Escrow {
+        let escrow = escrow.as_deref().ok_or(TixError::InvalidRefundRecipient)?;
+        let agent = agent.as_deref_mut().ok_or(TixError::InvalidRefundRecipient)?;
+        require!(escrow.key() == entry.funding_account, TixError::InvalidRefundRecipient);
+        require!(agent.key() == escrow.agent, TixError::InvalidRefundRecipient);
+        agent.tickets_purchased += 1;
+        agent.money_saved = agent.money_saved.checked_add(saved).ok_or(TixError::MathOverflow)?;
+    }
+
+    return_deposit(entry, remaining, owner, escrow, agent, delegate)?;
+
+    tier.waitlist_queued = tier.waitlist_queued.checked_sub(1).ok_or(TixError::MathUnderflow)?;
+    advance_head(waitlist);
+    Ok(())
+}
+
+/// Send `amount` of an entry's deposit back where it came from. An agent's
+/// escrow balance and budget get back what was reserved when it joined, and
+/// so does the spend cap of the delegate that queued it.
+fn return_deposit<'info>(
+    entry: &mut Account<'info, WaitlistEntry>,
+    amount: u64,
+    owner: &AccountInfo<'info>,
+    escrow: Option<&mut Account<'info, AgentEscrow>>,
+    agent: Option<&mut Account<'info, AIAgent>>,
+    delegate: Option<&AccountInfo<'info>>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    spend_deposit(entry, amount)?;
+
+    match entry.funding_source {
+        FundingSource::Wallet => {
+            **entry.to_account_info().try_borrow_mut_lamports()? -= amount;
+            **owner.try_borrow_mut_lamports()? += amount;
+        }
+        FundingSource::Escrow => {
+            let escrow = escrow.ok_or(TixError::InvalidRefundRecipient)?;
+            let agent = agent.ok_or(TixError::InvalidRefundRecipient)?;
+            require!(escrow.key() == entry.funding_account, TixError::InvalidRefundRecipient);
+            require!(agent.key() == escrow.agent, TixError::InvalidRefundRecipient);
+
+            **entry.to_account_info().try_borrow_mut_lamports()? -= amount;
+            **escrow.to_account_info().try_borrow_mut_lamports()? += amount;
+
+            restore_escrow_deposit(escrow, agent, amount)?;
+
+            // A delegate's cap was charged the full deposit when it joined
+            if entry.claimer != agent.owner {
+                let delegate = delegate.ok_or(TixError::InvalidDelegate)?;
+                credit_agent_spend(delegate, &agent.key(), &entry.claimer, amount)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// =====================================
+// CONTEXTS
+// =====================================
+
+#[derive(Accounts)]
+pub struct OpenWaitlist<'info> {
+    #[account(
+        seeds = [b"event", organizer.key().as_ref(), event.event_id.as_bytes()],
+        bump = event.bump
+    )]
+    pub event: Account<'info, Event>,
+
+    #[account(
+        seeds = [b"tier", event.key().as_ref(), tier.tier_id.as_bytes()],
+        bump = tier.bump
+    )]
+    pub tier: Account<'info, TicketTier>,
+
+    #[account(
+        init,
+        seeds = [b"waitlist", tier.key().as_ref()],
+        bump,
+        payer = organizer,
+        space = Waitlist::SPACE
+    )]
+    pub waitlist: Account<'info, Waitlist>,
+
+    #[account(
+        mut,
+        constraint = organizer.key() == event.organizer @ TixError::Unauthorized
+    )]
+    pub organizer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JoinWaitlist<'info> {
+    #[account(
+        constraint = event.key() == tier.event @ TixError::InvalidInput
+    )]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(mut)]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist", tier.key().as_ref()],
+        bump = waitlist.bump
+    )]
+    pub waitlist: Box<Account<'info, Waitlist>>,
+
+    #[account(
+        init,
+        seeds = [b"waitlist_entry", waitlist.key().as_ref(), waitlist.tail.to_le_bytes().as_ref()],
+        bump,
+        payer = wallet,
+        space = WaitlistEntry::SPACE
+    )]
+    pub entry: Box<Account<'info, WaitlistEntry>>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JoinWaitlistWithEscrow<'info> {
+    #[account(
+        constraint = event.key() == tier.event @ TixError::InvalidInput
+    )]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(mut)]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist", tier.key().as_ref()],
+        bump = waitlist.bump
+    )]
+    pub waitlist: Box<Account<'info, Waitlist>>,
+
+    #[account(
+        init,
+        seeds = [b"waitlist_entry", waitlist.key().as_ref(), waitlist.tail.to_le_bytes().as_ref()],
+        bump,
+        payer = authority,
+        space = WaitlistEntry::SPACE
+    )]
+    pub entry: Box<Account<'info, WaitlistEntry>>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.owner.as_ref(), agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Box<Account<'info, AIAgent>>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", agent.key().as_ref(), agent.owner.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Box<Account<'info, AgentEscrow>>,
+
+    /// Required unless the authority is the agent owner
+    #[account(
+        mut,
+        seeds = [b"delegate", agent.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, AgentDelegate>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveWaitlist<'info> {
+    #[account(mut)]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        seeds = [b"waitlist", tier.key().as_ref()],
+        bump = waitlist.bump
+    )]
+    pub waitlist: Box<Account<'info, Waitlist>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist_entry", waitlist.key().as_ref(), entry.position.to_le_bytes().as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Box<Account<'info, WaitlistEntry>>,
+
+    #[account(
+        mut,
+        constraint = owner.key() == entry.owner @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against entry, receives a wallet deposit back
+    pub owner: UncheckedAccount<'info>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(mut)]
+    pub escrow: Option<Account<'info, AgentEscrow>>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(mut)]
+    pub agent: Option<Account<'info, AIAgent>>,
+
+    #[account(mut)]
+    /// CHECK: The claimer's delegate PDA, verified when the deposit is
+    /// returned. Required when a delegate queued the entry.
+    pub delegate: Option<UncheckedAccount<'info>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OfferWaitlistSeat<'info> {
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist", tier.key().as_ref()],
+        bump = waitlist.bump
+    )]
+    pub waitlist: Box<Account<'info, Waitlist>>,
+
+    #[account(
+        seeds = [b"waitlist_entry", waitlist.key().as_ref(), waitlist.head.to_le_bytes().as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Box<Account<'info, WaitlistEntry>>,
+}
+
+#[derive(Accounts)]
+pub struct SkipWaitlistEntry<'info> {
+    #[account(mut)]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist", tier.key().as_ref()],
+        bump = waitlist.bump
+    )]
+    pub waitlist: Box<Account<'info, Waitlist>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist_entry", waitlist.key().as_ref(), waitlist.head.to_le_bytes().as_ref()],
+        bump = entry.bump,
+        close = owner
+    )]
+    pub entry: Box<Account<'info, WaitlistEntry>>,
+
+    #[account(
+        mut,
+        constraint = owner.key() == entry.owner @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against entry, receives the deposit and rent back
+    pub owner: UncheckedAccount<'info>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(mut)]
+    pub escrow: Option<Account<'info, AgentEscrow>>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(mut)]
+    pub agent: Option<Account<'info, AIAgent>>,
+
+    #[account(mut)]
+    /// CHECK: The claimer's delegate PDA, verified when the deposit is
+    /// returned. Required when a delegate queued the entry.
+    pub delegate: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWaitlistTicket<'info> {
+    #[account(mut)]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        seeds = [b"tier", event.key().as_ref(), tier.tier_id.as_bytes()],
+        bump = tier.bump
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist", tier.key().as_ref()],
+        bump = waitlist.bump
+    )]
+    pub waitlist: Box<Account<'info, Waitlist>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist_entry", waitlist.key().as_ref(), waitlist.head.to_le_bytes().as_ref()],
+        bump = entry.bump,
+        close = owner
+    )]
+    pub entry: Box<Account<'info, WaitlistEntry>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"user_ticket_counter", entry.owner.as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = UserTicketCounter::SPACE
+    )]
+    pub user_ticket_counter: Box<Account<'info, UserTicketCounter>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", event.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, EventVault>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against config
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 0,
+        mint::authority = event,
+    )]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Metadata account will be created via CPI
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        seeds = [b"ticket", ticket_mint.key().as_ref()],
+        bump,
+        payer = authority,
+        space = Ticket::SPACE
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        mut,
+        constraint = owner.key() == entry.owner @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against entry, receives the NFT and any unspent deposit
+    pub owner: UncheckedAccount<'info>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(mut)]
+    pub escrow: Option<Account<'info, AgentEscrow>>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(mut)]
+    pub agent: Option<Account<'info, AIAgent>>,
+
+    #[account(mut)]
+    /// CHECK: The claimer's delegate PDA, verified when the deposit is
+    /// returned. Required when a delegate queued the entry.
+    pub delegate: Option<UncheckedAccount<'info>>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(
+        init_if_needed,
+        seeds = [b"agent_ticket_counter", agent.as_ref().map(|a| a.key()).unwrap_or_default().as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = AgentTicketCounter::SPACE
+    )]
+    pub agent_ticket_counter: Option<Box<Account<'info, AgentTicketCounter>>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct FillWaitlistFromListing<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        constraint = event.key() == listing.event @ TixError::InvalidInput
+    )]
+    pub event: Box<Account<'info, Event>>,
+
+    #[account(
+        mut,
+        constraint = tier.key() == listing.tier @ TixError::InvalidInput
+    )]
+    pub tier: Box<Account<'info, TicketTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist", tier.key().as_ref()],
+        bump = waitlist.bump
+    )]
+    pub waitlist: Box<Account<'info, Waitlist>>,
+
+    #[account(
+        mut,
+        seeds = [b"waitlist_entry", waitlist.key().as_ref(), waitlist.head.to_le_bytes().as_ref()],
+        bump = entry.bump,
+        close = owner
+    )]
+    pub entry: Box<Account<'info, WaitlistEntry>>,
+
+    #[account(
+        mut,
+        seeds = [b"ticket", listing.ticket_mint.as_ref()],
+        bump = ticket.bump
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", listing.ticket_mint.as_ref()],
+        bump = listing.bump,
+        close = seller
+    )]
+    pub listing: Box<Account<'info, MarketListing>>,
+
+    #[account(
+        mut,
+        seeds = [b"listing_vault", listing.key().as_ref()],
+        bump
+    )]
+    pub listing_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = ticket_mint.key() == listing.ticket_mint @ TixError::InvalidInput
+    )]
+    pub ticket_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = owner.key() == entry.owner @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against entry, receives the NFT and any unspent deposit
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = seller.key() == listing.seller @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against listing
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against config
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = fee_recipient.key() == event.fee_recipient @ TixError::Unauthorized
+    )]
+    /// CHECK: Verified against event
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"user_ticket_counter", entry.owner.as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = UserTicketCounter::SPACE
+    )]
+    pub user_ticket_counter: Box<Account<'info, UserTicketCounter>>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(mut)]
+    pub escrow: Option<Account<'info, AgentEscrow>>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(mut)]
+    pub agent: Option<Account<'info, AIAgent>>,
+
+    #[account(mut)]
+    /// CHECK: The claimer's delegate PDA, verified when the deposit is
+    /// returned. Required when a delegate queued the entry.
+    pub delegate: Option<UncheckedAccount<'info>>,
+
+    /// Required when the deposit came from an agent escrow
+    #[account(
+        init_if_needed,
+        seeds = [b"agent_ticket_counter", agent.as_ref().map(|a| a.key()).unwrap_or_default().as_ref(), event.key().as_ref()],
+        bump,
+        payer = authority,
+        space = AgentTicketCounter::SPACE
+    )]
+    pub agent_ticket_counter: Option<Box<Account<'info, AgentTicketCounter>>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn waitlist(claim_window: i64) -> Waitlist {
+        Waitlist {
+            event: Pubkey::new_unique(),
+            tier: Pubkey::new_unique(),
+            head: 0,
+            tail: 3,
+            claim_window,
+            head_offered_at: 0,
+            bump: 255,
+        }
+    }
+
+    fn entry(funding_source: FundingSource, deposit: u64) -> WaitlistEntry {
+        WaitlistEntry {
+            waitlist: Pubkey::new_unique(),
+            position: 0,
+            owner: Pubkey::new_unique(),
+            claimer: Pubkey::new_unique(),
+            max_price: deposit,
+            deposit,
+            funding_source,
+            funding_account: Pubkey::new_unique(),
+            is_active: true,
+            joined_at: 0,
+            bump: 255,
+        }
+    }
+
+    fn agent(spent_budget: u64) -> AIAgent {
+        AIAgent {
+            owner: Pubkey::new_unique(),
+            agent_id: "agent".to_string(),
+            name: "Agent".to_string(),
+            is_active: true,
+            auto_purchase_enabled: true,
+            auto_purchase_threshold: 0,
+            max_budget_per_ticket: 1_000,
+            total_budget: 10_000,
+            spent_budget,
+            max_tickets_per_event: 0,
+            tickets_purchased: 0,
+            money_saved: 0,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    fn escrow(balance: u64, total_spent: u64) -> AgentEscrow {
+        AgentEscrow {
+            agent: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            balance,
+            total_deposited: balance + total_spent,
+            total_withdrawn: 0,
+            total_spent,
+            created_at: 0,
+            last_activity: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn head_advances_and_clears_the_offer() {
+        let mut waitlist = waitlist(60);
+        waitlist.head_offered_at = 1_000;
+
+        advance_head(&mut waitlist);
+        assert_eq!(waitlist.head, 1);
+        assert_eq!(waitlist.tail, 3);
+        assert_eq!(waitlist.head_offered_at, 0);
+
+        advance_head(&mut waitlist);
+        advance_head(&mut waitlist);
+        assert!(waitlist.is_empty());
+    }
+
+    #[test]
+    fn offer_lapses_only_after_the_claim_window() {
+        let mut timed = waitlist(60);
+        assert!(!timed.head_offer_lapsed(10_000));
+
+        timed.head_offered_at = 1_000;
+        assert!(!timed.head_offer_lapsed(1_060));
+        assert!(timed.head_offer_lapsed(1_061));
+
+        let mut open = waitlist(0);
+        open.head_offered_at = 1_000;
+        assert!(!open.head_offer_lapsed(10_000));
+    }
+
+    #[test]
+    fn active_affordable_head_is_not_skippable() {
+        let entry = entry(FundingSource::Wallet, 500);
+        assert!(!is_skippable(&entry, None, false, None));
+        assert!(!is_skippable(&entry, None, false, Some(500)));
+    }
+
+    #[test]
+    fn skips_left_lapsed_and_priced_out_heads() {
+        let mut left = entry(FundingSource::Wallet, 500);
+        left.is_active = false;
+        assert!(is_skippable(&left, None, false, None));
+
+        let entry = entry(FundingSource::Wallet, 500);
+        assert!(is_skippable(&entry, None, true, None));
+        assert!(is_skippable(&entry, None, false, Some(501)));
+    }
+
+    #[test]
+    fn skips_escrow_head_whose_agent_stopped_buying() {
+        let escrow_entry = entry(FundingSource::Escrow, 500);
+        assert!(!is_skippable(&escrow_entry, Some(&agent(500)), false, None));
+
+        let mut inactive = agent(500);
+        inactive.is_active = false;
+        assert!(is_skippable(&escrow_entry, Some(&inactive), false, None));
+
+        let mut paused = agent(500);
+        paused.auto_purchase_enabled = false;
+        assert!(is_skippable(&escrow_entry, Some(&paused), false, None));
+
+        // A wallet entry does not depend on any agent
+        let wallet = entry(FundingSource::Wallet, 500);
+        assert!(!is_skippable(&wallet, Some(&inactive), false, None));
+    }
+
+    #[test]
+    fn wallet_deposit_returns_what_the_seat_did_not_cost() {
+        let mut entry = entry(FundingSource::Wallet, 500);
+        assert_eq!(spend_deposit(&mut entry, 350).unwrap(), 150);
+        assert_eq!(spend_deposit(&mut entry, 150).unwrap(), 0);
+        assert!(spend_deposit(&mut entry, 1).is_err());
+    }
+
+    #[test]
+    fn escrow_deposit_restores_balance_and_budget() {
+        // Joined with a 500 deposit out of a 1_000 escrow
+        let mut entry = entry(FundingSource::Escrow, 500);
+        let mut escrow = escrow(500, 500);
+        let mut agent = agent(500);
+
+        let remaining = spend_deposit(&mut entry, 350).unwrap();
+        restore_escrow_deposit(&mut escrow, &mut agent, remaining).unwrap();
+
+        assert_eq!(escrow.balance, 650);
+        assert_eq!(escrow.total_spent, 350);
+        assert_eq!(agent.spent_budget, 350);
+    }
+
+    #[test]
+    fn escrow_deposit_returned_in_full_on_leave() {
+        let mut escrow = escrow(500, 500);
+        let mut agent = agent(500);
+
+        restore_escrow_deposit(&mut escrow, &mut agent, 500).unwrap();
+        assert_eq!(escrow.balance, 1_000);
+        assert_eq!(escrow.total_spent, 0);
+        assert_eq!(agent.spent_budget, 0);
+
+        assert!(restore_escrow_deposit(&mut escrow, &mut agent, 1).is_err());
+    }
+}
@@ -2,11 +2,24 @@ use anchor_lang::prelude::*;
 
 pub mod state;
 pub mod error;
+pub mod math;
+pub mod constraints;
+pub mod pricing;
 pub mod instructions;
 
+use constraints::{validate_event_schedule, validate_tier_window};
+use instructions::admin::*;
 use instructions::escrow::*;
-
-use anchor_spl::token;
+use instructions::token_escrow::*;
+use instructions::events::*;
+use instructions::delegate::*;
+use instructions::primary_market::*;
+use instructions::tickets::*;
+use instructions::checkin::*;
+use instructions::secondary_market::*;
+use instructions::waitlist::*;
+use instructions::refunds::*;
+use instructions::proceeds::*;
 
 declare_id!("EXZ9u1aF8gvHeUsKM8eTRzWDo88WGMKWZJLbvM8bYetJ");
 
@@ -15,54 +28,261 @@ pub mod pulse {
     use super::*;
     use crate::error::TixError;
 
-    /// =====================================
-    /// EVENT INSTRUCTIONS
-    /// =====================================
+    // =====================================
+    // ADMIN INSTRUCTIONS
+    // =====================================
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        protocol_fee_bps: u16,
+        default_price_cap_bps: u16,
+        min_listing_duration: i64,
+        max_listing_duration: i64,
+        allow_agent_coordination: bool,
+        require_verification: bool,
+    ) -> Result<()> {
+        instructions::admin::initialize_config(
+            ctx,
+            protocol_fee_bps,
+            default_price_cap_bps,
+            min_listing_duration,
+            max_listing_duration,
+            allow_agent_coordination,
+            require_verification,
+        )
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        instructions::admin::update_config(ctx, params)
+    }
+
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        instructions::admin::propose_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::admin::accept_admin(ctx)
+    }
+
+    // =====================================
+    // EVENT INSTRUCTIONS
+    // =====================================
 
     pub fn create_event(
         ctx: Context<CreateEvent>,
         event_id: String,
         organizer_fee_bps: u16,
+        metadata_uri: String,
+        schedule: EventSchedule,
+        settles_at: Option<i64>,
+        early_release_bps: Option<u16>,
     ) -> Result<()> {
+        validate_event_schedule(&schedule)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let settles_at = match settles_at {
+            Some(settles_at) => settles_at,
+            None => schedule
+                .event_end_time
+                .checked_add(config.default_settlement_delay)
+                .ok_or(TixError::MathOverflow)?,
+        };
+        let early_release_bps = early_release_bps.unwrap_or(config.default_early_release_bps);
+
+        require!(metadata_uri.len() <= Event::MAX_URI_LENGTH, TixError::InvalidInput);
+        require!(organizer_fee_bps <= 10000, TixError::InvalidFeeBps);
+        require!(
+            organizer_fee_bps as u32 + config.protocol_fee_bps as u32 <= 10000,
+            TixError::InvalidFeeBps
+        );
+        require!(settles_at > now, TixError::InvalidSettlementTime);
+        require!(early_release_bps <= 10000, TixError::InvalidFeeBps);
+
         let event = &mut ctx.accounts.event;
         event.organizer = ctx.accounts.organizer.key();
         event.event_id = event_id;
+        event.metadata_uri = metadata_uri;
         event.organizer_fee_bps = organizer_fee_bps;
+        event.royalty_bps = 0;
+        event.price_cap_bps = None;
+        event.fee_recipient = ctx.accounts.organizer.key();
+        event.max_tickets_per_wallet = config.default_max_tickets_per_wallet;
         event.is_active = true;
+        event.is_cancelled = false;
+        event.cancel_reason = 0;
+        event.cancelled_at = 0;
+        event.tier_count = 0;
+        event.tickets_refunded = 0;
+        event.settles_at = settles_at;
+        event.early_release_bps = early_release_bps;
+        event.refund_reserve = 0;
+        event.vaults_drawn = 0;
+        event.token_vaults = 0;
+        event.set_schedule(&schedule);
+        event.reentry_policy = ReentryPolicy::SingleEntry;
         event.total_tickets_sold = 0;
         event.total_revenue = 0;
-        event.created_at = Clock::get()?.unix_timestamp;
+        event.total_protocol_fees = 0;
+        event.total_organizer_fees = 0;
+        event.created_at = now;
         event.bump = ctx.bumps.event;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.event = event.key();
+        vault.balance = 0;
+        vault.total_funded = 0;
+        vault.total_refunded = 0;
+        vault.total_collected = 0;
+        vault.total_withdrawn = 0;
+        vault.fees_held = 0;
+        vault.total_fees_paid = 0;
+        vault.bump = ctx.bumps.vault;
         Ok(())
     }
 
-    /// =====================================
-    /// TICKET TIER INSTRUCTIONS
-    /// =====================================
+    pub fn pause_event(ctx: Context<ManageEvent>) -> Result<()> {
+        instructions::events::pause_event(ctx)
+    }
+
+    pub fn resume_event(ctx: Context<ManageEvent>) -> Result<()> {
+        instructions::events::resume_event(ctx)
+    }
+
+    pub fn update_event(
+        ctx: Context<ManageEvent>,
+        organizer_fee_bps: Option<u16>,
+        max_tickets_per_wallet: Option<u32>,
+        metadata_uri: Option<String>,
+        settles_at: Option<i64>,
+        early_release_bps: Option<u16>,
+        fee_recipient: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::events::update_event(
+            ctx,
+            organizer_fee_bps,
+            max_tickets_per_wallet,
+            metadata_uri,
+            settles_at,
+            early_release_bps,
+            fee_recipient,
+        )
+    }
+
+    pub fn set_resale_terms(
+        ctx: Context<ManageEvent>,
+        royalty_bps: Option<u16>,
+        price_cap_bps: Option<Option<u16>>,
+    ) -> Result<()> {
+        instructions::events::set_resale_terms(ctx, royalty_bps, price_cap_bps)
+    }
+
+    pub fn set_reentry_policy(ctx: Context<ManageEvent>, policy: ReentryPolicy) -> Result<()> {
+        instructions::events::set_reentry_policy(ctx, policy)
+    }
+
+    pub fn reschedule_event(ctx: Context<ManageEvent>, schedule: EventSchedule) -> Result<()> {
+        instructions::events::reschedule_event(ctx, schedule)
+    }
+
+    pub fn cancel_event(ctx: Context<ManageEvent>, reason_code: u8) -> Result<()> {
+        instructions::events::cancel_event(ctx, reason_code)
+    }
+
+    pub fn close_event(ctx: Context<CloseEvent>) -> Result<()> {
+        instructions::events::close_event(ctx)
+    }
+
+    // =====================================
+    // TICKET TIER INSTRUCTIONS
+    // =====================================
 
     pub fn create_ticket_tier(
         ctx: Context<CreateTicketTier>,
         tier_id: String,
         price: u64,
         max_supply: u64,
+        sale_window: Option<SaleWindow>,
     ) -> Result<()> {
         let tier = &mut ctx.accounts.tier;
         require!(ctx.accounts.event.is_active, TixError::EventNotActive);
 
+        match sale_window {
+            Some(window) => {
+                validate_tier_window(&ctx.accounts.event, &window)?;
+                tier.sale_start_time = window.start_time;
+                tier.sale_end_time = window.end_time;
+            }
+            None => {
+                tier.sale_start_time = 0;
+                tier.sale_end_time = 0;
+            }
+        }
+
         tier.event = ctx.accounts.event.key();
         tier.tier_id = tier_id;
         tier.price = price;
         tier.max_supply = max_supply;
         tier.current_supply = 0;
         tier.is_active = true;
+        tier.is_cancelled = false;
+        tier.tickets_refunded = 0;
+        tier.revenue = 0;
+        tier.collected = 0;
+        tier.price_schedule = PriceSchedule::Fixed;
+        tier.refunded_amount = 0;
+        tier.fees_held = 0;
+        tier.payment_mint = ctx.accounts.payment_mint.as_ref().map_or(Pubkey::default(), |mint| mint.key());
+        tier.reference_pricing = ReferencePricing::FaceValue;
+        tier.resale_floor = 0;
+        tier.resale_floor_at = 0;
+        tier.resale_twap = 0;
+        tier.last_resale_at = 0;
+        tier.waitlist_queued = 0;
         tier.bump = ctx.bumps.tier;
+
+        let event = &mut ctx.accounts.event;
+        event.tier_count = event.tier_count.checked_add(1).ok_or(TixError::MathOverflow)?;
         Ok(())
     }
 
-    /// =====================================
-    /// AI AGENT INSTRUCTIONS
-    /// =====================================
+    pub fn cancel_ticket_tier(ctx: Context<ManageTier>) -> Result<()> {
+        instructions::events::cancel_ticket_tier(ctx)
+    }
+
+    pub fn update_tier_price(ctx: Context<ManageTier>, new_price: u64, allow_repricing: bool) -> Result<()> {
+        instructions::events::update_tier_price(ctx, new_price, allow_repricing)
+    }
 
+    pub fn set_tier_price_schedule(
+        ctx: Context<ManageTier>,
+        schedule: PriceSchedule,
+        allow_repricing: bool,
+    ) -> Result<()> {
+        instructions::events::set_tier_price_schedule(ctx, schedule, allow_repricing)
+    }
+
+    pub fn set_reference_pricing(ctx: Context<ManageTier>, reference: ReferencePricing) -> Result<()> {
+        instructions::events::set_reference_pricing(ctx, reference)
+    }
+
+    pub fn resize_tier(ctx: Context<ManageTier>, new_max_supply: u64) -> Result<()> {
+        instructions::events::resize_tier(ctx, new_max_supply)
+    }
+
+    pub fn set_tier_active(ctx: Context<ManageTier>, active: bool) -> Result<()> {
+        instructions::events::set_tier_active(ctx, active)
+    }
+
+    pub fn close_ticket_tier(ctx: Context<CloseTicketTier>) -> Result<()> {
+        instructions::events::close_ticket_tier(ctx)
+    }
+
+    // =====================================
+    // AI AGENT INSTRUCTIONS
+    // =====================================
+
+    #[allow(clippy::too_many_arguments)]
     pub fn create_ai_agent(
         ctx: Context<CreateAIAgent>,
         agent_id: String,
@@ -73,6 +293,8 @@ pub mod pulse {
         auto_purchase_threshold: u16,    
         max_tickets_per_event: u32,      
     ) -> Result<()> {
+        require!(auto_purchase_threshold <= 10000, TixError::InvalidInput);
+
         let agent = &mut ctx.accounts.agent;
         let clock = Clock::get()?;
 
@@ -87,15 +309,16 @@ pub mod pulse {
         agent.max_tickets_per_event = max_tickets_per_event; 
         agent.spent_budget = 0;
         agent.tickets_purchased = 0;
+        agent.money_saved = 0;
         agent.created_at = clock.unix_timestamp;
         agent.bump = ctx.bumps.agent;
 
         Ok(())
     }
 
-    /// =====================================
-    /// AGENT CONTROL INSTRUCTIONS
-    /// =====================================
+    // =====================================
+    // AGENT CONTROL INSTRUCTIONS
+    // =====================================
 
     pub fn activate_agent(ctx: Context<ActivateAgent>) -> Result<()> {
         let agent = &mut ctx.accounts.agent;
@@ -157,9 +380,31 @@ pub mod pulse {
         Ok(())
     }
 
-    /// =====================================
-    /// ESCROW INSTRUCTIONS
-    /// =====================================
+    // =====================================
+    // AGENT DELEGATE INSTRUCTIONS
+    // =====================================
+
+    pub fn add_agent_delegate(
+        ctx: Context<AddAgentDelegate>,
+        delegate_key: Pubkey,
+        scopes: u8,
+        spend_cap: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::delegate::add_agent_delegate(ctx, delegate_key, scopes, spend_cap, expires_at)
+    }
+
+    pub fn revoke_agent_delegate(ctx: Context<RevokeAgentDelegate>) -> Result<()> {
+        instructions::delegate::revoke_agent_delegate(ctx)
+    }
+
+    pub fn rotate_agent_delegate(ctx: Context<RotateAgentDelegate>, new_delegate_key: Pubkey) -> Result<()> {
+        instructions::delegate::rotate_agent_delegate(ctx, new_delegate_key)
+    }
+
+    // =====================================
+    // ESCROW INSTRUCTIONS
+    // =====================================
 
     pub fn create_escrow(ctx: Context<CreateEscrow>) -> Result<()> {
         instructions::escrow::create_escrow(ctx)
@@ -173,9 +418,37 @@ pub mod pulse {
         instructions::escrow::withdraw_from_escrow(ctx, amount)
     }
 
-    /// =====================================
-    /// CORE FUNCTION: BUY TICKET WITH AGENT ESCROW
-    /// =====================================
+    pub fn create_token_escrow(ctx: Context<CreateTokenEscrow>, max_price_per_ticket: u64) -> Result<()> {
+        instructions::token_escrow::create_token_escrow(ctx, max_price_per_ticket)
+    }
+
+    pub fn deposit_to_token_escrow(ctx: Context<DepositToTokenEscrow>, amount: u64) -> Result<()> {
+        instructions::token_escrow::deposit_to_token_escrow(ctx, amount)
+    }
+
+    pub fn withdraw_from_token_escrow(ctx: Context<WithdrawFromTokenEscrow>, amount: u64) -> Result<()> {
+        instructions::token_escrow::withdraw_from_token_escrow(ctx, amount)
+    }
+
+    // =====================================
+    // PRIMARY MARKET: QUOTE & DIRECT WALLET PURCHASE
+    // =====================================
+
+    pub fn quote_ticket_price(ctx: Context<QuoteTicketPrice>, tier_id: String) -> Result<u64> {
+        instructions::primary_market::quote_ticket_price(ctx, tier_id)
+    }
+
+    pub fn buy_ticket(ctx: Context<BuyTicket>, tier_id: String) -> Result<()> {
+        instructions::primary_market::buy_ticket(ctx, tier_id)
+    }
+
+    pub fn buy_ticket_with_token(ctx: Context<BuyTicketWithToken>, tier_id: String) -> Result<()> {
+        instructions::primary_market::buy_ticket_with_token(ctx, tier_id)
+    }
+
+    // =====================================
+    // CORE FUNCTION: BUY TICKET WITH AGENT ESCROW
+    // =====================================
 
     pub fn buy_ticket_with_escrow(
         ctx: Context<BuyTicketWithEscrow>,
@@ -185,65 +458,261 @@ pub mod pulse {
         instructions::escrow::buy_ticket_with_escrow(ctx, tier_id, agent_owner)
     }
 
+    /// Debit escrow and mint the ticket NFT to the agent owner in one instruction
+    pub fn buy_and_mint_with_escrow(
+        ctx: Context<BuyAndMintWithEscrow>,
+        tier_id: String,
+        agent_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::escrow::buy_and_mint_with_escrow(ctx, tier_id, agent_owner)
+    }
+
+    /// Token-priced counterpart of `buy_and_mint_with_escrow`
+    pub fn buy_and_mint_with_token_escrow(
+        ctx: Context<BuyAndMintWithTokenEscrow>,
+        tier_id: String,
+        agent_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::token_escrow::buy_and_mint_with_token_escrow(ctx, tier_id, agent_owner)
+    }
+
+    // =====================================
+    // TICKET INSTRUCTIONS
+    // =====================================
+
+    pub fn assign_seat(ctx: Context<ManageTicket>, seat_info: Option<String>) -> Result<()> {
+        instructions::tickets::assign_seat(ctx, seat_info)
+    }
+
+    // =====================================
+    // CHECK-IN INSTRUCTIONS
+    // =====================================
+
+    pub fn add_gate_operator(ctx: Context<AddGateOperator>, operator: Pubkey) -> Result<()> {
+        instructions::checkin::add_gate_operator(ctx, operator)
+    }
+
+    pub fn remove_gate_operator(ctx: Context<RemoveGateOperator>) -> Result<()> {
+        instructions::checkin::remove_gate_operator(ctx)
+    }
+
+    /// Check-in backed by a holder signature verified through the Ed25519 program
+    pub fn check_in_with_signature(ctx: Context<SignedGateScan>, nonce: u64, expires_at: i64) -> Result<()> {
+        instructions::checkin::check_in_with_signature(ctx, nonce, expires_at)
+    }
+
+    pub fn check_out_ticket(ctx: Context<GateScan>) -> Result<()> {
+        instructions::checkin::check_out_ticket(ctx)
+    }
+
+    // =====================================
+    // SECONDARY MARKET INSTRUCTIONS
+    // =====================================
+
+    pub fn list_ticket(ctx: Context<ListTicket>, list_price: u64, duration: i64) -> Result<()> {
+        instructions::secondary_market::list_ticket(ctx, list_price, duration)
+    }
+
+    pub fn list_ticket_dutch(
+        ctx: Context<ListTicket>,
+        start_price: u64,
+        min_price: u64,
+        decay_bps: u16,
+        decay_interval: i64,
+        duration: i64,
+    ) -> Result<()> {
+        instructions::secondary_market::list_ticket_dutch(
+            ctx,
+            start_price,
+            min_price,
+            decay_bps,
+            decay_interval,
+            duration,
+        )
+    }
+
+    pub fn update_listing(ctx: Context<UpdateListing>, list_price: u64) -> Result<()> {
+        instructions::secondary_market::update_listing(ctx, list_price)
+    }
+
+    pub fn cancel_listing(ctx: Context<CloseListing>) -> Result<()> {
+        instructions::secondary_market::cancel_listing(ctx)
+    }
+
+    pub fn reclaim_expired_listing(ctx: Context<CloseListing>) -> Result<()> {
+        instructions::secondary_market::reclaim_expired_listing(ctx)
+    }
+
+    pub fn buy_listed_ticket(ctx: Context<BuyListedTicket>, max_price: u64) -> Result<()> {
+        instructions::secondary_market::buy_listed_ticket(ctx, max_price)
+    }
+
+    pub fn buy_listed_ticket_with_escrow(
+        ctx: Context<BuyListedTicketWithEscrow>,
+        max_price: u64,
+    ) -> Result<()> {
+        instructions::secondary_market::buy_listed_ticket_with_escrow(ctx, max_price)
+    }
+
+    pub fn make_offer(ctx: Context<MakeOffer>, amount: u64, duration: i64) -> Result<()> {
+        instructions::secondary_market::make_offer(ctx, amount, duration)
+    }
+
+    pub fn cancel_offer(ctx: Context<CloseOffer>) -> Result<()> {
+        instructions::secondary_market::cancel_offer(ctx)
+    }
+
+    pub fn reclaim_expired_offer(ctx: Context<CloseOffer>) -> Result<()> {
+        instructions::secondary_market::reclaim_expired_offer(ctx)
+    }
+
+    pub fn accept_offer(ctx: Context<AcceptOffer>) -> Result<()> {
+        instructions::secondary_market::accept_offer(ctx)
+    }
+
+    // =====================================
+    // WAITLIST INSTRUCTIONS
+    // =====================================
+
+    pub fn open_waitlist(ctx: Context<OpenWaitlist>, claim_window: i64) -> Result<()> {
+        instructions::waitlist::open_waitlist(ctx, claim_window)
+    }
+
+    pub fn join_waitlist(ctx: Context<JoinWaitlist>, max_price: u64) -> Result<()> {
+        instructions::waitlist::join_waitlist(ctx, max_price)
+    }
+
+    pub fn join_waitlist_with_escrow(ctx: Context<JoinWaitlistWithEscrow>, max_price: u64) -> Result<()> {
+        instructions::waitlist::join_waitlist_with_escrow(ctx, max_price)
+    }
+
+    pub fn leave_waitlist(ctx: Context<LeaveWaitlist>) -> Result<()> {
+        instructions::waitlist::leave_waitlist(ctx)
+    }
+
+    pub fn offer_waitlist_seat(ctx: Context<OfferWaitlistSeat>) -> Result<()> {
+        instructions::waitlist::offer_waitlist_seat(ctx)
+    }
+
+    pub fn skip_waitlist_entry(ctx: Context<SkipWaitlistEntry>) -> Result<()> {
+        instructions::waitlist::skip_waitlist_entry(ctx)
+    }
+
+    pub fn claim_waitlist_ticket(ctx: Context<ClaimWaitlistTicket>) -> Result<()> {
+        instructions::waitlist::claim_waitlist_ticket(ctx)
+    }
+
+    pub fn fill_waitlist_from_listing(ctx: Context<FillWaitlistFromListing>) -> Result<()> {
+        instructions::waitlist::fill_waitlist_from_listing(ctx)
+    }
+
+    // =====================================
+    // REFUND INSTRUCTIONS
+    // =====================================
+
+    pub fn fund_refund_vault(ctx: Context<FundRefundVault>, amount: u64) -> Result<()> {
+        instructions::refunds::fund_refund_vault(ctx, amount)
+    }
+
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        instructions::refunds::claim_refund(ctx)
+    }
+
+    pub fn claim_escrow_refund(ctx: Context<ClaimEscrowRefund>) -> Result<()> {
+        instructions::refunds::claim_escrow_refund(ctx)
+    }
+
+    pub fn fund_token_refund_vault(ctx: Context<FundTokenRefundVault>, amount: u64) -> Result<()> {
+        instructions::refunds::fund_token_refund_vault(ctx, amount)
+    }
+
+    pub fn claim_token_refund(ctx: Context<ClaimTokenRefund>) -> Result<()> {
+        instructions::refunds::claim_token_refund(ctx)
+    }
+
+    // =====================================
+    // PROCEEDS INSTRUCTIONS
+    // =====================================
+
+    pub fn withdraw_proceeds(ctx: Context<WithdrawProceeds>, amount: u64) -> Result<()> {
+        instructions::proceeds::withdraw_proceeds(ctx, amount)
+    }
+
+    pub fn open_token_vault(ctx: Context<OpenTokenVault>) -> Result<()> {
+        instructions::proceeds::open_token_vault(ctx)
+    }
+
+    pub fn withdraw_token_proceeds(ctx: Context<WithdrawTokenProceeds>, amount: u64) -> Result<()> {
+        instructions::proceeds::withdraw_token_proceeds(ctx, amount)
+    }
+
+    pub fn release_organizer_fees(ctx: Context<ReleaseOrganizerFees>) -> Result<()> {
+        instructions::proceeds::release_organizer_fees(ctx)
+    }
+
+    pub fn release_token_organizer_fees(ctx: Context<ReleaseTokenOrganizerFees>) -> Result<()> {
+        instructions::proceeds::release_token_organizer_fees(ctx)
+    }
+
+    pub fn close_token_vault(ctx: Context<CloseTokenVault>) -> Result<()> {
+        instructions::proceeds::close_token_vault(ctx)
+    }
+
     /// =====================================
     /// CORE FUNCTION: MINT TICKET NFT
     /// =====================================
-    pub fn mint_ticket_nft(ctx: Context<MintTicketNFT>, name: String, symbol: String, uri: String) -> Result<()> {
+    pub fn mint_ticket_nft(ctx: Context<MintTicketNFT>) -> Result<()> {
         let event = &ctx.accounts.event;
-        let organizer_key = event.organizer.key();
-        let event_id_bytes = event.event_id.as_bytes();
-        let bump_vector = event.bump.to_le_bytes();
-        
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            b"event",
-            organizer_key.as_ref(),
-            event_id_bytes,
-            &bump_vector,
-        ]];
-
-        anchor_spl::token::mint_to(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                anchor_spl::token::MintTo {
-                    mint: ctx.accounts.ticket_mint.to_account_info(),
-                    to: ctx.accounts.buyer_token_account.to_account_info(),
-                    authority: ctx.accounts.event.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            1,
-        )?;
-
-        let metadata_infos = anchor_spl::metadata::CreateMetadataAccountsV3 {
-            metadata: ctx.accounts.metadata.to_account_info(),
-            mint: ctx.accounts.ticket_mint.to_account_info(),
-            mint_authority: ctx.accounts.event.to_account_info(),
-            payer: ctx.accounts.authority.to_account_info(),
-            update_authority: ctx.accounts.event.to_account_info(),
-            system_program: ctx.accounts.system_program.to_account_info(),
-            rent: ctx.accounts.rent.to_account_info(),
-        };
-
-        let data_v2 = anchor_spl::metadata::mpl_token_metadata::types::DataV2 {
-            name,
-            symbol,
-            uri,
-            seller_fee_basis_points: 0,
-            creators: None,
-            collection: None,
-            uses: None,
-        };
-
-        anchor_spl::metadata::create_metadata_accounts_v3(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_metadata_program.to_account_info(),
-                metadata_infos,
-                signer_seeds,
-            ),
-            data_v2,
-            true, 
-            true, 
-            None,
+        let tier = &ctx.accounts.tier;
+        let receipt = ctx
+            .accounts
+            .receipt
+            .as_ref()
+            .ok_or(TixError::PurchaseReceiptRequired)?;
+
+        require!(receipt.event == event.key(), TixError::PurchaseReceiptMismatch);
+        require!(receipt.tier == tier.key(), TixError::PurchaseReceiptMismatch);
+        require!(receipt.buyer == ctx.accounts.buyer.key(), TixError::PurchaseReceiptMismatch);
+
+        ctx.accounts.ticket.set_inner(Ticket {
+            mint: ctx.accounts.ticket_mint.key(),
+            event: receipt.event,
+            tier: receipt.tier,
+            original_price: receipt.price,
+            payment_mint: Pubkey::default(),
+            funding_source: FundingSource::Escrow,
+            funding_account: receipt.escrow,
+            purchase_price: receipt.price,
+            refund_amount: receipt.refund_amount,
+            organizer_fee: receipt.organizer_fee,
+            status: TicketStatus::Active,
+            purchased_at: receipt.purchased_at,
+            validated_at: None,
+            seat_info: None,
+            entry_count: 0,
+            is_inside: false,
+            last_entry_at: 0,
+            last_exit_at: 0,
+            last_checkin_nonce: 0,
+            bump: ctx.bumps.ticket,
+        });
+
+        instructions::nft::mint_ticket(
+            instructions::nft::TicketMintAccounts {
+                event: &event.to_account_info(),
+                ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+                metadata: &ctx.accounts.metadata.to_account_info(),
+                recipient_token_account: &ctx.accounts.buyer_token_account.to_account_info(),
+                payer: &ctx.accounts.authority.to_account_info(),
+                token_program: &ctx.accounts.token_program.to_account_info(),
+                token_metadata_program: &ctx.accounts.token_metadata_program.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+                rent: &ctx.accounts.rent.to_account_info(),
+            },
+            event,
+            instructions::nft::ticket_name(event, tier),
+            instructions::nft::TICKET_SYMBOL.to_string(),
+            event.metadata_uri.clone(),
         )?;
 
         msg!("Verified NFT Minted!");
@@ -274,6 +743,22 @@ pub struct CreateEvent<'info> {
         space = Event::SPACE
     )]
     pub event: Account<'info, Event>,
+    #[account(
+        init,
+        seeds = [
+            b"vault",
+            event.key().as_ref()
+        ],
+        bump,
+        payer = organizer,
+        space = EventVault::SPACE
+    )]
+    pub vault: Account<'info, EventVault>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
     #[account(mut)]
     pub organizer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -283,6 +768,7 @@ pub struct CreateEvent<'info> {
 #[instruction(tier_id: String)]
 pub struct CreateTicketTier<'info> {
     #[account(
+        mut,
         constraint = event.is_active @ TixError::EventNotActive
     )]
     pub event: Account<'info, Event>,
@@ -298,6 +784,8 @@ pub struct CreateTicketTier<'info> {
         space = TicketTier::SPACE
     )]
     pub tier: Account<'info, TicketTier>,
+    /// SPL mint the tier is priced in; omit to price in lamports
+    pub payment_mint: Option<Account<'info, anchor_spl::token::Mint>>,
     #[account(
         mut,
         constraint = organizer.key() == event.organizer @ TixError::Unauthorized
@@ -445,6 +933,8 @@ pub struct MintTicketNFT<'info> {
     #[account(mut)]
     pub event: Account<'info, Event>,
 
+    pub tier: Account<'info, TicketTier>,
+
     #[account(
         init,
         payer = authority,
@@ -468,14 +958,33 @@ pub struct MintTicketNFT<'info> {
     /// CHECK: The user receiving the NFT
     pub buyer: UncheckedAccount<'info>,
 
+    /// Receipt from buy_ticket_with_escrow, closed to its payer once the ticket is minted
+    #[account(
+        mut,
+        constraint = receipt.payer == receipt_payer.key() @ TixError::PurchaseReceiptMismatch,
+        close = receipt_payer
+    )]
+    pub receipt: Option<Account<'info, PurchaseReceipt>>,
+
+    #[account(mut)]
+    /// CHECK: Verified against the receipt, gets its rent back
+    pub receipt_payer: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        seeds = [b"ticket", ticket_mint.key().as_ref()],
+        bump,
+        payer = authority,
+        space = Ticket::SPACE
+    )]
+    pub ticket: Account<'info, Ticket>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
     pub token_program: Program<'info, anchor_spl::token::Token>,
     pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
-    
-    /// CHECK: Metaplex program ID
-    pub token_metadata_program: UncheckedAccount<'info>,
+    pub token_metadata_program: Program<'info, anchor_spl::metadata::Metadata>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use crate::error::TixError;
+
+// Math utility functions with overflow protection
+
+pub const BPS_DENOMINATOR: u64 = 10000;
+
+/// `amount * bps / 10000`, rounded down
+pub fn calculate_percentage(amount: u64, bps: u16) -> Result<u64> {
+    let result = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(TixError::MathOverflow)?
+        .checked_div(BPS_DENOMINATOR as u128)
+        .ok_or(TixError::MathOverflow)?;
+
+    Ok(result as u64)
+}
+
+/// How one primary sale is divided up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SaleSplit {
+    pub protocol_fee: u64,
+    pub organizer_fee: u64,
+    pub net: u64,
+}
+
+/// Split a sale price into the protocol fee, the event's fee-recipient share
+/// and the net proceeds. Both fees round down; any dust stays in `net`, so
+/// the three parts always add back up to `price`.
+pub fn split_sale(price: u64, protocol_fee_bps: u16, organizer_fee_bps: u16) -> Result<SaleSplit> {
+    require!(
+        protocol_fee_bps as u64 + organizer_fee_bps as u64 <= BPS_DENOMINATOR,
+        TixError::InvalidFeeBps
+    );
+
+    let protocol_fee = calculate_percentage(price, protocol_fee_bps)?;
+    let organizer_fee = calculate_percentage(price, organizer_fee_bps)?;
+    let net = price
+        .checked_sub(protocol_fee)
+        .and_then(|rest| rest.checked_sub(organizer_fee))
+        .ok_or(TixError::MathUnderflow)?;
+
+    Ok(SaleSplit { protocol_fee, organizer_fee, net })
+}
+
+impl SaleSplit {
+    /// What the event vault takes in: the net proceeds plus the organizer
+    /// fee, which is held until settlement so a refund can return it
+    pub fn held(&self) -> u64 {
+        // Both parts come out of the price, so the sum cannot overflow
+        self.net + self.organizer_fee
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentage_rounds_down() {
+        assert_eq!(calculate_percentage(10_000, 250).unwrap(), 250);
+        assert_eq!(calculate_percentage(999, 100).unwrap(), 9);
+        assert_eq!(calculate_percentage(1, 9_999).unwrap(), 0);
+        assert_eq!(calculate_percentage(u64::MAX, 10_000).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn split_leaves_dust_in_net() {
+        let split = split_sale(1_001, 250, 500).unwrap();
+        assert_eq!(split, SaleSplit { protocol_fee: 25, organizer_fee: 50, net: 926 });
+        assert_eq!(split.protocol_fee + split.organizer_fee + split.net, 1_001);
+    }
+
+    #[test]
+    fn split_always_sums_to_price() {
+        for price in [0, 1, 7, 999, 1_000_000_007, u64::MAX] {
+            for (protocol, organizer) in [(0, 0), (1, 1), (333, 667), (5_000, 5_000), (10_000, 0)] {
+                let split = split_sale(price, protocol, organizer).unwrap();
+                assert_eq!(split.protocol_fee as u128 + split.organizer_fee as u128 + split.net as u128, price as u128);
+            }
+        }
+    }
+
+    #[test]
+    fn vault_holds_everything_but_the_protocol_fee() {
+        let split = split_sale(1_001, 250, 500).unwrap();
+        assert_eq!(split.held(), 976);
+        assert_eq!(split_sale(u64::MAX, 0, 10_000).unwrap().held(), u64::MAX);
+    }
+
+    #[test]
+    fn split_rejects_fees_over_full_price() {
+        assert!(split_sale(1_000, 6_000, 4_001).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use anchor_lang::prelude::*;
+use crate::error::TixError;
+use crate::state::{ListingPricing, PriceSchedule, PriceStep, TicketTier};
+
+// Tier price helpers. Every purchase path prices through `effective_price`.
+
+pub const MAX_PRICE_STEPS: usize = 4;
+
+/// Window over which the resale floor holds and the resale TWAP averages
+pub const RESALE_REFERENCE_WINDOW: i64 = 24 * 60 * 60;
+
+/// Fixed-point scale for exponential curve factors
+const CURVE_SCALE: u128 = 1_000_000_000;
+
+/// Price of the next ticket in `tier` at time `now`
+pub fn effective_price(tier: &TicketTier, now: i64) -> Result<u64> {
+    match &tier.price_schedule {
+        PriceSchedule::Fixed => Ok(tier.price),
+        PriceSchedule::Steps { steps } => Ok(step_price(tier.price, steps, now)),
+        PriceSchedule::Linear { start_time, end_time, start_price, end_price } => {
+            linear_price(*start_time, *end_time, *start_price, *end_price, now)
+        }
+        PriceSchedule::LinearCurve { increment, cap } => {
+            linear_curve_price(tier.price, *increment, *cap, tier.current_supply)
+        }
+        PriceSchedule::ExponentialCurve { growth_bps, cap } => {
+            exponential_curve_price(tier.price, *growth_bps, *cap, tier.current_supply)
+        }
+    }
+}
+
+pub fn validate_price_schedule(schedule: &PriceSchedule, base_price: u64) -> Result<()> {
+    match schedule {
+        PriceSchedule::Fixed => {}
+        PriceSchedule::Steps { steps } => {
+            require!(!steps.is_empty(), TixError::InvalidPriceSchedule);
+            require!(steps[0].start_time >= 0, TixError::InvalidPriceSchedule);
+            require!(steps.len() <= MAX_PRICE_STEPS, TixError::InvalidPriceSchedule);
+            require!(
+                steps.windows(2).all(|pair| pair[0].start_time < pair[1].start_time),
+                TixError::InvalidPriceSchedule
+            );
+        }
+        PriceSchedule::Linear { start_time, end_time, .. } => {
+            require!(*start_time >= 0, TixError::InvalidPriceSchedule);
+            require!(end_time > start_time, TixError::InvalidPriceSchedule);
+        }
+        PriceSchedule::LinearCurve { increment, cap } => {
+            require!(*increment > 0, TixError::InvalidPriceSchedule);
+            require!(*cap >= base_price, TixError::InvalidPriceSchedule);
+        }
+        PriceSchedule::ExponentialCurve { growth_bps, cap } => {
+            require!(*growth_bps > 0, TixError::InvalidPriceSchedule);
+            require!(base_price > 0, TixError::InvalidPriceSchedule);
+            require!(*cap >= base_price, TixError::InvalidPriceSchedule);
+        }
+    }
+    Ok(())
+}
+
+/// Dutch listings must decay toward a floor below their start price
+pub fn validate_listing_pricing(pricing: &ListingPricing, start_price: u64) -> Result<()> {
+    if let ListingPricing::Dutch { min_price, decay_bps, decay_interval } = *pricing {
+        require!(min_price > 0, TixError::InvalidPriceSchedule);
+        require!(min_price < start_price, TixError::InvalidPriceSchedule);
+        require!(decay_bps > 0 && decay_bps <= 10000, TixError::InvalidPriceSchedule);
+        require!(decay_interval > 0, TixError::InvalidPriceSchedule);
+    }
+    Ok(())
+}
+
+/// `start_price` less `decay_bps` of it for every full `decay_interval`
+/// elapsed, never below `min_price`. The discount rounds down.
+pub fn dutch_auction_price(
+    start_price: u64,
+    min_price: u64,
+    decay_bps: u16,
+    decay_interval: i64,
+    elapsed: i64,
+) -> u64 {
+    if elapsed <= 0 || decay_interval <= 0 {
+        return start_price;
+    }
+
+    let intervals = (elapsed / decay_interval) as u128;
+    let discount_bps = intervals.saturating_mul(decay_bps as u128).min(10000);
+    let discount = start_price as u128 * discount_bps / 10000;
+
+    (start_price - discount as u64).max(min_price)
+}
+
+/// Lowest resale seen within the last window. A resale at or under the
+/// floor, or the first one after the window lapses, sets a new floor.
+pub fn resale_floor(floor: u64, floor_at: i64, price: u64, now: i64) -> (u64, i64) {
+    if floor_at == 0 || price <= floor || now.saturating_sub(floor_at) >= RESALE_REFERENCE_WINDOW {
+        (price, now)
+    } else {
+        (floor, floor_at)
+    }
+}
+
+/// Time-weighted average resale price. Each resale moves the average toward
+/// its price by the share of the window elapsed since the previous resale.
+pub fn resale_twap(twap: u64, last_resale_at: i64, price: u64, now: i64) -> u64 {
+    if last_resale_at == 0 {
+        return price;
+    }
+
+    let window = RESALE_REFERENCE_WINDOW as u128;
+    let elapsed = now.saturating_sub(last_resale_at).clamp(0, RESALE_REFERENCE_WINDOW) as u128;
+    ((twap as u128 * (window - elapsed) + price as u128 * elapsed) / window) as u64
+}
+
+/// Last step that has started; the tier's base price before the first one
+fn step_price(base_price: u64, steps: &[PriceStep], now: i64) -> u64 {
+    steps
+        .iter()
+        .rev()
+        .find(|step| now >= step.start_time)
+        .map_or(base_price, |step| step.price)
+}
+
+/// Straight line from `start_price` to `end_price`, flat outside the ramp.
+/// Rounds toward `start_price`.
+fn linear_price(start_time: i64, end_time: i64, start_price: u64, end_price: u64, now: i64) -> Result<u64> {
+    if now <= start_time {
+        return Ok(start_price);
+    }
+    if now >= end_time {
+        return Ok(end_price);
+    }
+
+    let elapsed = (now - start_time) as u128;
+    let duration = (end_time - start_time) as u128;
+    let (low, high) = (start_price.min(end_price) as u128, start_price.max(end_price) as u128);
+    let delta = (high - low)
+        .checked_mul(elapsed)
+        .ok_or(TixError::MathOverflow)?
+        .checked_div(duration)
+        .ok_or(TixError::MathOverflow)?;
+
+    let price = if end_price >= start_price { low + delta } else { high - delta };
+    Ok(price as u64)
+}
+
+/// `base + increment * sold`, capped
+fn linear_curve_price(base_price: u64, increment: u64, cap: u64, sold: u64) -> Result<u64> {
+    let price = (increment as u128)
+        .checked_mul(sold as u128)
+        .ok_or(TixError::MathOverflow)?
+        .checked_add(base_price as u128)
+        .ok_or(TixError::MathOverflow)?;
+
+    Ok(price.min(cap as u128) as u64)
+}
+
+/// `base * (1 + growth_bps / 10000) ^ sold`, capped and rounded down.
+/// Uses square-and-multiply on a 1e9 fixed-point factor; any intermediate
+/// past the cap is clamped, since it can only push the result to the cap.
+fn exponential_curve_price(base_price: u64, growth_bps: u16, cap: u64, sold: u64) -> Result<u64> {
+    if base_price == 0 || base_price >= cap {
+        return Ok(base_price.min(cap));
+    }
+
+    // Smallest factor that already reaches the cap
+    let limit = (cap as u128)
+        .checked_mul(CURVE_SCALE)
+        .ok_or(TixError::MathOverflow)?
+        .checked_div(base_price as u128)
+        .ok_or(TixError::MathOverflow)?
+        .checked_add(1)
+        .ok_or(TixError::MathOverflow)?;
+    let clamped_mul = |a: u128, b: u128| a.checked_mul(b).map_or(limit, |v| (v / CURVE_SCALE).min(limit));
+
+    let mut factor = CURVE_SCALE;
+    let mut step = CURVE_SCALE + growth_bps as u128 * CURVE_SCALE / 10000;
+    let mut exponent = sold;
+
+    while exponent > 0 && factor < limit {
+        if exponent & 1 == 1 {
+            factor = clamped_mul(factor, step);
+        }
+        step = clamped_mul(step, step);
+        exponent >>= 1;
+    }
+
+    let price = (base_price as u128)
+        .checked_mul(factor)
+        .ok_or(TixError::MathOverflow)?
+        .checked_div(CURVE_SCALE)
+        .ok_or(TixError::MathOverflow)?;
+
+    Ok(price.min(cap as u128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps() -> Vec<PriceStep> {
+        vec![
+            PriceStep { start_time: 100, price: 10 },
+            PriceStep { start_time: 200, price: 20 },
+            PriceStep { start_time: 300, price: 30 },
+        ]
+    }
+
+    #[test]
+    fn steps_use_latest_started_step() {
+        assert_eq!(step_price(5, &steps(), 99), 5);
+        assert_eq!(step_price(5, &steps(), 100), 10);
+        assert_eq!(step_price(5, &steps(), 299), 20);
+        assert_eq!(step_price(5, &steps(), 1_000), 30);
+    }
+
+    #[test]
+    fn linear_ramp_is_flat_outside_window() {
+        assert_eq!(linear_price(100, 200, 1_000, 2_000, 50).unwrap(), 1_000);
+        assert_eq!(linear_price(100, 200, 1_000, 2_000, 250).unwrap(), 2_000);
+    }
+
+    #[test]
+    fn linear_ramp_rounds_toward_start_price() {
+        assert_eq!(linear_price(0, 3, 0, 10, 1).unwrap(), 3);
+        assert_eq!(linear_price(0, 3, 10, 0, 1).unwrap(), 7);
+        assert_eq!(linear_price(0, 100, 1_000, 2_000, 50).unwrap(), 1_500);
+    }
+
+    #[test]
+    fn linear_ramp_handles_full_range() {
+        assert_eq!(linear_price(0, 2, 0, u64::MAX, 1).unwrap(), u64::MAX / 2);
+    }
+
+    #[test]
+    fn rejects_unordered_steps() {
+        let mut unordered = steps();
+        unordered.swap(0, 1);
+        assert!(validate_price_schedule(&PriceSchedule::Steps { steps: unordered }, 5).is_err());
+        assert!(validate_price_schedule(&PriceSchedule::Steps { steps: steps() }, 5).is_ok());
+    }
+
+    #[test]
+    fn linear_curve_rises_with_supply_until_cap() {
+        assert_eq!(linear_curve_price(100, 10, 1_000, 0).unwrap(), 100);
+        assert_eq!(linear_curve_price(100, 10, 1_000, 5).unwrap(), 150);
+        assert_eq!(linear_curve_price(100, 10, 1_000, 500).unwrap(), 1_000);
+        assert_eq!(linear_curve_price(100, u64::MAX, 1_000, u64::MAX).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn exponential_curve_compounds_and_rounds_down() {
+        // 10% per ticket
+        assert_eq!(exponential_curve_price(1_000, 1_000, u64::MAX, 0).unwrap(), 1_000);
+        assert_eq!(exponential_curve_price(1_000, 1_000, u64::MAX, 1).unwrap(), 1_100);
+        assert_eq!(exponential_curve_price(1_000, 1_000, u64::MAX, 2).unwrap(), 1_210);
+        assert_eq!(exponential_curve_price(1_000, 1_000, u64::MAX, 3).unwrap(), 1_331);
+        assert_eq!(exponential_curve_price(1_000, 1_000, u64::MAX, 10).unwrap(), 2_593);
+    }
+
+    #[test]
+    fn exponential_curve_is_capped() {
+        assert_eq!(exponential_curve_price(1_000, 1_000, 5_000, 100).unwrap(), 5_000);
+        assert_eq!(exponential_curve_price(1_000, 10_000, u64::MAX, u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(exponential_curve_price(1, 1, u64::MAX, u64::MAX).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn dutch_price_drops_per_full_interval() {
+        assert_eq!(dutch_auction_price(1_000, 100, 500, 60, 0), 1_000);
+        assert_eq!(dutch_auction_price(1_000, 100, 500, 60, 59), 1_000);
+        assert_eq!(dutch_auction_price(1_000, 100, 500, 60, 60), 950);
+        assert_eq!(dutch_auction_price(1_000, 100, 500, 60, 179), 900);
+    }
+
+    #[test]
+    fn dutch_price_stops_at_floor() {
+        assert_eq!(dutch_auction_price(1_000, 100, 500, 60, 60 * 18), 100);
+        assert_eq!(dutch_auction_price(1_000, 100, 500, 60, i64::MAX), 100);
+        assert_eq!(dutch_auction_price(u64::MAX, 1, 10_000, 1, 1), 1);
+    }
+
+    #[test]
+    fn resale_floor_holds_for_a_window() {
+        assert_eq!(resale_floor(0, 0, 500, 10), (500, 10));
+        assert_eq!(resale_floor(500, 10, 600, 20), (500, 10));
+        assert_eq!(resale_floor(500, 10, 400, 20), (400, 20));
+        assert_eq!(resale_floor(500, 10, 600, 10 + RESALE_REFERENCE_WINDOW), (600, 10 + RESALE_REFERENCE_WINDOW));
+    }
+
+    #[test]
+    fn resale_twap_weights_by_elapsed_time() {
+        assert_eq!(resale_twap(0, 0, 1_000, 10), 1_000);
+        assert_eq!(resale_twap(1_000, 10, 2_000, 10), 1_000);
+        assert_eq!(resale_twap(1_000, 10, 2_000, 10 + RESALE_REFERENCE_WINDOW / 2), 1_500);
+        assert_eq!(resale_twap(1_000, 10, 2_000, 10 + 2 * RESALE_REFERENCE_WINDOW), 2_000);
+    }
+}
@@ -1,28 +1,135 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
 
-/// =====================================
-/// EVENT (Minimal - rest offchain in Supabase)
-/// =====================================
+// =====================================
+// GLOBAL CONFIG
+// =====================================
+
+#[account]
+pub struct GlobalConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,          // Proposed admin, default key when none
+    pub protocol_fee_bps: u16,          // Protocol fee on ticket sales
+    pub default_price_cap_bps: u16,     // Default max price increase (anti-scalping)
+    pub min_listing_duration: i64,      // Minimum listing duration (seconds)
+    pub max_listing_duration: i64,      // Maximum listing duration (seconds)
+    pub allow_agent_coordination: bool, // Feature flag
+    pub require_verification: bool,     // KYC requirement flag
+    pub treasury: Pubkey,               // Protocol treasury for fee collection
+    pub default_settlement_delay: i64,  // Seconds after the event ends before proceeds settle
+    pub default_early_release_bps: u16, // Default share of proceeds released early
+    pub default_max_tickets_per_wallet: u32, // 0 = unlimited
+    pub bump: u8,
+}
+
+impl GlobalConfig {
+    pub const SPACE: usize = 8 + 32 + 32 + 2 + 2 + 8 + 8 + 1 + 1 + 32 + 8 + 2 + 4 + 1 + 32;
+}
+
+// =====================================
+// EVENT (Minimal - rest offchain in Supabase)
+// =====================================
 
 #[account]
 pub struct Event {
     pub organizer: Pubkey,
     pub event_id: String,      
-    pub organizer_fee_bps: u16,
+    pub metadata_uri: String,
+    pub organizer_fee_bps: u16,      // share of each sale paid to fee_recipient
+    pub royalty_bps: u16,            // share of each resale paid to fee_recipient
+    pub price_cap_bps: Option<u16>,  // max resale markup over face value; None uses the config default
+    pub fee_recipient: Pubkey,
+    pub max_tickets_per_wallet: u32,
     pub total_tickets_sold: u64,
     pub total_revenue: u64,
+    pub total_protocol_fees: u64,
+    pub total_organizer_fees: u64,
     pub is_active: bool,
+    pub is_cancelled: bool,
+    pub cancel_reason: u8,
+    pub cancelled_at: i64,
+    pub tier_count: u32,
+    pub tickets_refunded: u64,
+    pub settles_at: i64,             // proceeds fully withdrawable from here
+    pub early_release_bps: u16,      // share of proceeds withdrawable before settles_at
+    pub refund_reserve: u64,         // proceeds held back for cancelled tiers
+    pub vaults_drawn: u32,           // proceeds vaults with withdrawals not yet funded back; blocks cancelling
+    pub token_vaults: u32,           // per-mint token vaults still open; blocks closing
+    pub event_start_time: i64,
+    pub event_end_time: i64,
+    pub sale_start_time: i64,
+    pub sale_end_time: i64,
+    pub reentry_policy: ReentryPolicy,
     pub created_at: i64,
     pub bump: u8,
 }
 
 impl Event {
-    pub const SPACE: usize = 8 + 32 + (4 + 32) + 2 + 8 + 8 + 1 + 8 + 1 + 32;
+    pub const MAX_URI_LENGTH: usize = 200;
+    pub const SPACE: usize = 8 + 32 + (4 + 32) + (4 + Self::MAX_URI_LENGTH) + 2 + 2 + (1 + 2) + 32 + 4 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 8 + 4 + 8 + 8 + 2 + 8 + 4 + 4 + 8 + 8 + 8 + 8 + ReentryPolicy::SPACE + 8 + 1 + 32;
+
+    pub fn set_schedule(&mut self, schedule: &EventSchedule) {
+        self.event_start_time = schedule.event_start_time;
+        self.event_end_time = schedule.event_end_time;
+        self.sale_start_time = schedule.sale_start_time;
+        self.sale_end_time = schedule.sale_end_time;
+    }
+
+    /// Settlement time after moving the event to end at `event_end_time`. The
+    /// delay after the end is kept, and settlement never moves earlier.
+    pub fn rescheduled_settlement(&self, event_end_time: i64) -> Option<i64> {
+        let delay = self.settles_at.checked_sub(self.event_end_time)?;
+        Some(event_end_time.checked_add(delay)?.max(self.settles_at))
+    }
+
+    /// Doors are open between the event's start and end times
+    pub fn is_ongoing(&self, now: i64) -> bool {
+        now >= self.event_start_time && now <= self.event_end_time
+    }
+
+    /// Resale markup cap for this event, falling back to the protocol default
+    pub fn resale_price_cap_bps(&self, config: &GlobalConfig) -> u16 {
+        self.price_cap_bps.unwrap_or(config.default_price_cap_bps)
+    }
+
+    /// Sold tickets on a cancelled event are owed a refund until claimed
+    pub fn has_pending_refunds(&self) -> bool {
+        self.is_cancelled && self.total_tickets_sold > self.tickets_refunded
+    }
+}
+
+/// Whether a ticket can be scanned in again after leaving the venue
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ReentryPolicy {
+    SingleEntry,
+    Unlimited,
+    Limited { max_entries: u16 },
 }
 
-/// =====================================
-/// TICKET TIER
-/// =====================================
+impl ReentryPolicy {
+    pub const SPACE: usize = 1 + 2;
+
+    pub fn allows_entry(&self, entry_count: u16) -> bool {
+        match self {
+            ReentryPolicy::SingleEntry => entry_count == 0,
+            ReentryPolicy::Unlimited => true,
+            ReentryPolicy::Limited { max_entries } => entry_count < *max_entries,
+        }
+    }
+}
+
+/// Event and sale times, unix seconds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct EventSchedule {
+    pub event_start_time: i64,
+    pub event_end_time: i64,
+    pub sale_start_time: i64,
+    pub sale_end_time: i64,
+}
+
+// =====================================
+// TICKET TIER
+// =====================================
 
 #[account]
 pub struct TicketTier {
@@ -32,16 +139,124 @@ pub struct TicketTier {
     pub max_supply: u64,
     pub current_supply: u64,
     pub is_active: bool,
+    pub is_cancelled: bool,
+    pub tickets_refunded: u64,
+    pub revenue: u64,                // sum of prices actually paid
+    pub collected: u64,              // what the vault received for those sales, organizer fees included
+    pub refunded_amount: u64,        // paid back out of `collected`
+    pub fees_held: u64,              // organizer fees in `collected` from tickets not yet refunded
+    pub sale_start_time: i64,        // 0 = follow the event's sale window
+    pub sale_end_time: i64,
+    pub price_schedule: PriceSchedule,
+    pub payment_mint: Pubkey,        // Pubkey::default() = priced in lamports
+    pub reference_pricing: ReferencePricing,
+    pub resale_floor: u64,           // lowest resale in the current window
+    pub resale_floor_at: i64,
+    pub resale_twap: u64,            // time-weighted average resale price
+    pub last_resale_at: i64,         // 0 = never resold
+    pub waitlist_queued: u64,        // waitlist entries still waiting; free seats go to them first
     pub bump: u8,
 }
 
 impl TicketTier {
-    pub const SPACE: usize = 8 + 32 + (4 + 20) + 8 + 8 + 8 + 1 + 1 + 32;
+    pub const SPACE: usize = 8 + 32 + (4 + 20) + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + PriceSchedule::SPACE + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 32;
+
+    pub fn is_sol_priced(&self) -> bool {
+        self.payment_mint == Pubkey::default()
+    }
+
+    pub fn has_sale_window(&self) -> bool {
+        self.sale_start_time != 0 || self.sale_end_time != 0
+    }
+
+    pub fn is_refundable(&self, event: &Event) -> bool {
+        event.is_cancelled || self.is_cancelled
+    }
+
+    pub fn has_pending_refunds(&self, event: &Event) -> bool {
+        self.is_refundable(event) && self.current_supply > self.tickets_refunded
+    }
+
+    /// Proceeds from tickets not yet refunded; what refunds can still claim
+    pub fn outstanding_revenue(&self) -> Option<u64> {
+        self.collected.checked_sub(self.refunded_amount)
+    }
+
+    /// Seats not held by a live ticket. Refunded tickets give their seat back.
+    pub fn seats_left(&self) -> u64 {
+        let issued = self.current_supply.saturating_sub(self.tickets_refunded);
+        self.max_supply.saturating_sub(issued)
+    }
+
+    /// Price agent purchases are measured against. Face value until the tier
+    /// has resold at least once.
+    pub fn reference_price(&self, now: i64) -> Result<u64> {
+        if self.last_resale_at == 0 {
+            return crate::pricing::effective_price(self, now);
+        }
+
+        match self.reference_pricing {
+            ReferencePricing::FaceValue => crate::pricing::effective_price(self, now),
+            ReferencePricing::ResaleFloor => Ok(self.resale_floor),
+            ReferencePricing::ResaleTwap => Ok(self.resale_twap),
+        }
+    }
+
+    /// Fold a completed resale into the tracked floor and TWAP
+    pub fn record_resale(&mut self, price: u64, now: i64) {
+        (self.resale_floor, self.resale_floor_at) =
+            crate::pricing::resale_floor(self.resale_floor, self.resale_floor_at, price, now);
+        self.resale_twap = crate::pricing::resale_twap(self.resale_twap, self.last_resale_at, price, now);
+        self.last_resale_at = now;
+    }
+}
+
+/// Which price a tier's agent purchases are measured against
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ReferencePricing {
+    FaceValue,
+    ResaleFloor,
+    ResaleTwap,
+}
+
+/// Per-tier sale window override, unix seconds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SaleWindow {
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+/// How a tier's price moves over time or with demand. `TicketTier.price` is the base price.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PriceSchedule {
+    Fixed,
+    /// Each step's price applies from its start time; base price before the first
+    Steps { steps: Vec<PriceStep> },
+    /// Linear ramp between two prices, flat outside the window
+    Linear { start_time: i64, end_time: i64, start_price: u64, end_price: u64 },
+    /// Base price plus `increment` per ticket already sold, up to `cap`
+    LinearCurve { increment: u64, cap: u64 },
+    /// Base price grown by `growth_bps` per ticket already sold, up to `cap`
+    ExponentialCurve { growth_bps: u16, cap: u64 },
+}
+
+impl PriceSchedule {
+    pub const SPACE: usize = 1 + 4 + crate::pricing::MAX_PRICE_STEPS * PriceStep::SPACE;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PriceStep {
+    pub start_time: i64,
+    pub price: u64,
+}
+
+impl PriceStep {
+    pub const SPACE: usize = 8 + 8;
 }
 
-/// =====================================
-/// AI AGENT (Simplified)
-/// =====================================
+// =====================================
+// AI AGENT (Simplified)
+// =====================================
 
 #[account]
 pub struct AIAgent {
@@ -56,17 +271,18 @@ pub struct AIAgent {
     pub spent_budget: u64,
     pub max_tickets_per_event: u32,  
     pub tickets_purchased: u64,
+    pub money_saved: u64,            // lamports paid under the tier reference price
     pub created_at: i64,
     pub bump: u8,
 }
 
 impl AIAgent {
-    pub const SPACE: usize = 8 + 32 + (4 + 30) + (4 + 50) + 1 + 1 + 2 + 8 + 8 + 8 + 4 + 8 + 8 + 1 + 32;
+    pub const SPACE: usize = 8 + 32 + (4 + 30) + (4 + 50) + 1 + 1 + 2 + 8 + 8 + 8 + 4 + 8 + 8 + 8 + 1 + 32;
 }
 
-/// =====================================
-/// AGENT ESCROW
-/// =====================================
+// =====================================
+// AGENT ESCROW
+// =====================================
 
 #[account]
 pub struct AgentEscrow {
@@ -84,3 +300,638 @@ pub struct AgentEscrow {
 impl AgentEscrow {
     pub const SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 32;
 }
+
+// =====================================
+// TOKEN ESCROW (SPL-token budget per agent and mint)
+// =====================================
+
+#[account]
+pub struct TokenEscrow {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub balance: u64,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub total_spent: u64,
+    pub max_price_per_ticket: u64,   // 0 = no per-ticket limit
+    pub created_at: i64,
+    pub last_activity: i64,
+    pub bump: u8,
+}
+
+impl TokenEscrow {
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 32;
+}
+
+// =====================================
+// EVENT VAULT (Sale proceeds and refund float)
+// =====================================
+
+#[account]
+pub struct EventVault {
+    pub event: Pubkey,
+    pub balance: u64,
+    pub total_funded: u64,
+    pub total_refunded: u64,
+    pub total_collected: u64,        // sales received, organizer fees included
+    pub total_withdrawn: u64,
+    pub fees_held: u64,              // organizer fees in `balance` owed to the fee recipient at settlement
+    pub total_fees_paid: u64,
+    pub bump: u8,
+}
+
+impl EventVault {
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 32;
+
+    /// Balance implied by the vault's ledger; must match `balance`
+    pub fn expected_balance(&self) -> Option<u64> {
+        self.total_funded
+            .checked_add(self.total_collected)?
+            .checked_sub(self.total_refunded)?
+            .checked_sub(self.total_withdrawn)?
+            .checked_sub(self.total_fees_paid)
+    }
+
+    /// Ledger agrees with itself, with the event's revenue net of protocol
+    /// fees and with the lamports held
+    pub fn is_reconciled(&self, event: &Event, lamports: u64, rent_exempt: u64) -> bool {
+        let collected = event.total_revenue.checked_sub(event.total_protocol_fees);
+
+        collected == Some(self.total_collected)
+            && self.expected_balance() == Some(self.balance)
+            && lamports >= rent_exempt.saturating_add(self.balance)
+    }
+
+    /// More has been paid out than the organizer put in, so some refunds are unfunded
+    pub fn is_drawn(&self) -> bool {
+        self.total_withdrawn.saturating_add(self.total_fees_paid) > self.total_funded
+    }
+}
+
+// =====================================
+// EVENT TOKEN VAULT (Proceeds and refund float per payment mint)
+// =====================================
+
+#[account]
+pub struct EventTokenVault {
+    pub event: Pubkey,
+    pub mint: Pubkey,
+    pub balance: u64,
+    pub total_funded: u64,
+    pub total_revenue: u64,          // gross sales, before fees
+    pub total_protocol_fees: u64,
+    pub total_organizer_fees: u64,
+    pub total_collected: u64,        // sales received, organizer fees included
+    pub total_refunded: u64,
+    pub total_withdrawn: u64,
+    pub refund_reserve: u64,         // proceeds held back for cancelled tiers
+    pub fees_held: u64,              // organizer fees in `balance` owed to the fee recipient at settlement
+    pub total_fees_paid: u64,
+    pub bump: u8,
+}
+
+impl EventTokenVault {
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 32;
+
+    pub fn expected_balance(&self) -> Option<u64> {
+        self.total_funded
+            .checked_add(self.total_collected)?
+            .checked_sub(self.total_refunded)?
+            .checked_sub(self.total_withdrawn)?
+            .checked_sub(self.total_fees_paid)
+    }
+
+    pub fn is_reconciled(&self, token_amount: u64) -> bool {
+        let collected = self.total_revenue.checked_sub(self.total_protocol_fees);
+
+        collected == Some(self.total_collected)
+            && self.expected_balance() == Some(self.balance)
+            && token_amount >= self.balance
+    }
+
+    pub fn is_drawn(&self) -> bool {
+        self.total_withdrawn.saturating_add(self.total_fees_paid) > self.total_funded
+    }
+}
+
+// =====================================
+// TICKET (Program record per minted NFT)
+// =====================================
+
+#[account]
+pub struct Ticket {
+    pub mint: Pubkey,
+    pub event: Pubkey,
+    pub tier: Pubkey,
+    pub original_price: u64,
+    pub payment_mint: Pubkey,        // Pubkey::default() = paid in lamports
+    pub funding_source: FundingSource,
+    pub funding_account: Pubkey,
+    pub purchase_price: u64,         // what funding_account paid; a refund unwinds at most this much spending
+    pub refund_amount: u64,          // what the vault received for the primary sale; a refund pays this back
+    pub organizer_fee: u64,          // part of `refund_amount` held for the fee recipient until settlement
+    pub status: TicketStatus,
+    pub purchased_at: i64,
+    pub validated_at: Option<i64>,   // first check-in
+    pub seat_info: Option<String>,   // e.g. "Section A, Row 5, Seat 12"
+    pub entry_count: u16,
+    pub is_inside: bool,
+    pub last_entry_at: i64,
+    pub last_exit_at: i64,
+    pub last_checkin_nonce: u64,     // highest nonce used in a holder-signed check-in
+    pub bump: u8,
+}
+
+impl Ticket {
+    pub const MAX_SEAT_INFO_LENGTH: usize = 50;
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 8 + 32 + 1 + 32 + 8 + 8 + 8 + 1 + 8 + (1 + 8) + (1 + 4 + Self::MAX_SEAT_INFO_LENGTH) + 2 + 1 + 8 + 8 + 8 + 1 + 32;
+
+    /// Ownership is whoever's token account holds the NFT; the record keeps no owner
+    pub fn is_held_by(&self, token_account: &TokenAccount, owner: &Pubkey) -> bool {
+        token_account.mint == self.mint && token_account.owner == *owner && token_account.amount == 1
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FundingSource {
+    Wallet,     // Paid directly by funding_account
+    Escrow,     // Paid from the AgentEscrow or TokenEscrow at funding_account
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TicketStatus {
+    Active,     // Ticket can be used
+    Consumed,   // Scanned at the venue
+    Refunded,   // Refund paid out, ticket is void
+}
+
+// =====================================
+// GATE OPERATOR (Per-event check-in authority)
+// =====================================
+
+#[account]
+pub struct GateOperator {
+    pub event: Pubkey,
+    pub operator: Pubkey,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+impl GateOperator {
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 1 + 32;
+}
+
+// =====================================
+// MARKET LISTING (Secondary market, NFT held in a listing vault)
+// =====================================
+
+#[account]
+pub struct MarketListing {
+    pub seller: Pubkey,
+    pub ticket_mint: Pubkey,
+    pub event: Pubkey,
+    pub tier: Pubkey,
+    pub list_price: u64,             // asking price; the start price for Dutch listings
+    pub pricing: ListingPricing,
+    pub original_price: u64,         // face value from the Ticket
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl MarketListing {
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 32 + 8 + ListingPricing::SPACE + 8 + 8 + 8 + 1 + 32;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+
+    /// Price a buyer pays at time `now`
+    pub fn current_price(&self, now: i64) -> u64 {
+        match self.pricing {
+            ListingPricing::Fixed => self.list_price,
+            ListingPricing::Dutch { min_price, decay_bps, decay_interval } => {
+                crate::pricing::dutch_auction_price(
+                    self.list_price,
+                    min_price,
+                    decay_bps,
+                    decay_interval,
+                    now.saturating_sub(self.created_at),
+                )
+            }
+        }
+    }
+}
+
+/// How a listing's asking price moves while it is open
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ListingPricing {
+    Fixed,
+    /// Drops by `decay_bps` of the start price every `decay_interval` seconds, down to `min_price`
+    Dutch { min_price: u64, decay_bps: u16, decay_interval: i64 },
+}
+
+impl ListingPricing {
+    pub const SPACE: usize = 1 + 8 + 2 + 8;
+}
+
+// =====================================
+// OFFER (Bid on a ticket, lamports locked in the offer account)
+// =====================================
+
+#[account]
+pub struct Offer {
+    pub bidder: Pubkey,
+    pub ticket_mint: Pubkey,
+    pub event: Pubkey,
+    pub amount: u64,                 // held in this account on top of its rent
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Offer {
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 32;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+}
+
+// =====================================
+// WAITLIST (FIFO queue for a sold-out tier)
+// =====================================
+
+#[account]
+pub struct Waitlist {
+    pub event: Pubkey,
+    pub tier: Pubkey,
+    pub head: u64,                   // position of the entry served next
+    pub tail: u64,                   // position the next joiner gets
+    pub claim_window: i64,           // seconds the head has to claim a free seat; 0 = anyone may fill it
+    pub head_offered_at: i64,        // when the head was offered a seat; 0 = not offered
+    pub bump: u8,
+}
+
+impl Waitlist {
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 32;
+
+    pub fn is_empty(&self) -> bool {
+        self.head == self.tail
+    }
+
+    /// Whether the head's claim window ran out without a claim
+    pub fn head_offer_lapsed(&self, now: i64) -> bool {
+        self.claim_window > 0
+            && self.head_offered_at != 0
+            && now > self.head_offered_at.saturating_add(self.claim_window)
+    }
+}
+
+#[account]
+pub struct WaitlistEntry {
+    pub waitlist: Pubkey,
+    pub position: u64,
+    pub owner: Pubkey,               // receives the ticket and the entry rent
+    pub claimer: Pubkey,             // signer allowed to claim during a claim window
+    pub max_price: u64,
+    pub deposit: u64,                // lamports held in this account on top of its rent
+    pub funding_source: FundingSource,
+    pub funding_account: Pubkey,     // wallet or AgentEscrow the deposit returns to
+    pub is_active: bool,             // false once the entrant has left
+    pub joined_at: i64,
+    pub bump: u8,
+}
+
+impl WaitlistEntry {
+    pub const SPACE: usize = 8 + 32 + 8 + 32 + 32 + 8 + 8 + 1 + 32 + 1 + 8 + 1 + 32;
+}
+
+// =====================================
+// TICKET COUNTERS (Per-event caps)
+// =====================================
+
+#[account]
+pub struct AgentTicketCounter {
+    pub agent: Pubkey,
+    pub event: Pubkey,
+    pub ticket_count: u32,
+    pub bump: u8,
+}
+
+impl AgentTicketCounter {
+    pub const SPACE: usize = 8 + 32 + 32 + 4 + 1 + 32;
+}
+
+#[account]
+pub struct UserTicketCounter {
+    pub user: Pubkey,
+    pub event: Pubkey,
+    pub ticket_count: u32,
+    pub bump: u8,
+}
+
+impl UserTicketCounter {
+    pub const SPACE: usize = 8 + 32 + 32 + 4 + 1 + 32;
+}
+
+// =====================================
+// PURCHASE RECEIPT (Consumed by mint_ticket_nft)
+// =====================================
+
+#[account]
+pub struct PurchaseReceipt {
+    pub event: Pubkey,
+    pub tier: Pubkey,
+    pub buyer: Pubkey,
+    pub escrow: Pubkey,
+    pub price: u64,
+    pub refund_amount: u64,          // what the vault received, organizer fee included
+    pub organizer_fee: u64,
+    pub payer: Pubkey,               // paid the receipt's rent; refunded when it is closed
+    pub purchased_at: i64,
+    pub slot: u64,
+    pub bump: u8,
+}
+
+impl PurchaseReceipt {
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 32 + 8 + 8 + 1 + 32;
+}
+
+// =====================================
+// AGENT DELEGATE (Scoped executor key)
+// =====================================
+
+#[account]
+pub struct AgentDelegate {
+    pub agent: Pubkey,
+    pub delegate: Pubkey,
+    pub scopes: u8,
+    pub spend_cap: u64,
+    pub spent: u64,
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl AgentDelegate {
+    pub const SPACE: usize = 8 + 32 + 32 + 1 + 8 + 8 + 8 + 8 + 1 + 32;
+
+    /// Allowed to spend escrow on primary ticket purchases
+    pub const SCOPE_BUY_PRIMARY: u8 = 1 << 0;
+    /// Allowed to spend escrow on resale listings
+    pub const SCOPE_BUY_RESALE: u8 = 1 << 1;
+    pub const ALL_SCOPES: u8 = Self::SCOPE_BUY_PRIMARY | Self::SCOPE_BUY_RESALE;
+
+    pub fn is_live(&self, now: i64) -> bool {
+        now < self.expires_at
+    }
+
+    pub fn has_scope(&self, scope: u8) -> bool {
+        self.scopes & scope == scope
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RENT: u64 = 1_000;
+
+    /// Event that sold 1_000 lamports of tickets: 25 to the protocol, 75 to the organizer
+    fn event() -> Event {
+        Event {
+            organizer: Pubkey::new_unique(),
+            event_id: "event".to_string(),
+            metadata_uri: String::new(),
+            organizer_fee_bps: 0,
+            royalty_bps: 0,
+            price_cap_bps: None,
+            fee_recipient: Pubkey::new_unique(),
+            max_tickets_per_wallet: 0,
+            total_tickets_sold: 10,
+            total_revenue: 1_000,
+            total_protocol_fees: 25,
+            total_organizer_fees: 75,
+            is_active: true,
+            is_cancelled: false,
+            cancel_reason: 0,
+            cancelled_at: 0,
+            tier_count: 0,
+            tickets_refunded: 0,
+            settles_at: 0,
+            early_release_bps: 0,
+            refund_reserve: 0,
+            vaults_drawn: 0,
+            token_vaults: 0,
+            event_start_time: 0,
+            event_end_time: 0,
+            sale_start_time: 0,
+            sale_end_time: 0,
+            reentry_policy: ReentryPolicy::SingleEntry,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    /// Vault that collected the event's 975 after the protocol fee, was funded 100,
+    /// refunded 200, paid out 300 and released the 75 organizer fees
+    fn vault() -> EventVault {
+        EventVault {
+            event: Pubkey::new_unique(),
+            balance: 500,
+            total_funded: 100,
+            total_refunded: 200,
+            total_collected: 975,
+            total_withdrawn: 300,
+            fees_held: 0,
+            total_fees_paid: 75,
+            bump: 255,
+        }
+    }
+
+    fn token_vault() -> EventTokenVault {
+        EventTokenVault {
+            event: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            balance: 500,
+            total_funded: 100,
+            total_revenue: 1_000,
+            total_protocol_fees: 25,
+            total_organizer_fees: 75,
+            total_collected: 975,
+            total_refunded: 200,
+            total_withdrawn: 300,
+            refund_reserve: 0,
+            fees_held: 0,
+            total_fees_paid: 75,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn vault_ledger_reconciles() {
+        let vault = vault();
+        assert_eq!(vault.expected_balance(), Some(500));
+        assert!(vault.is_reconciled(&event(), RENT + 500, RENT));
+        assert!(vault.is_reconciled(&event(), RENT + 600, RENT));
+    }
+
+    #[test]
+    fn vault_short_of_lamports_is_not_reconciled() {
+        assert!(!vault().is_reconciled(&event(), RENT + 499, RENT));
+    }
+
+    #[test]
+    fn vault_ledger_drift_is_not_reconciled() {
+        let mut off_balance = vault();
+        off_balance.balance = 499;
+        assert!(!off_balance.is_reconciled(&event(), RENT + 500, RENT));
+
+        let mut off_revenue = event();
+        off_revenue.total_revenue = 1_001;
+        assert!(!vault().is_reconciled(&off_revenue, RENT + 500, RENT));
+    }
+
+    #[test]
+    fn vault_is_drawn_once_withdrawals_pass_funding() {
+        let mut vault = vault();
+        assert!(vault.is_drawn());
+
+        // Released organizer fees count as paid out too
+        vault.total_funded = 300;
+        assert!(vault.is_drawn());
+
+        vault.total_funded = 375;
+        assert!(!vault.is_drawn());
+
+        vault.total_withdrawn = 2_000;
+        assert_eq!(vault.expected_balance(), None);
+    }
+
+    #[test]
+    fn token_vault_ledger_reconciles() {
+        let vault = token_vault();
+        assert_eq!(vault.expected_balance(), Some(500));
+        assert!(vault.is_reconciled(500));
+        assert!(!vault.is_reconciled(499));
+
+        let mut off_fees = token_vault();
+        off_fees.total_protocol_fees = 26;
+        assert!(!off_fees.is_reconciled(500));
+
+        let mut off_balance = token_vault();
+        off_balance.balance = 501;
+        assert!(!off_balance.is_reconciled(501));
+    }
+
+    fn ticket() -> Ticket {
+        Ticket {
+            mint: Pubkey::new_unique(),
+            event: Pubkey::new_unique(),
+            tier: Pubkey::new_unique(),
+            original_price: 1_000,
+            payment_mint: Pubkey::new_unique(),
+            funding_source: FundingSource::Wallet,
+            funding_account: Pubkey::new_unique(),
+            purchase_price: 1_000,
+            refund_amount: 975,
+            organizer_fee: 75,
+            status: TicketStatus::Active,
+            purchased_at: 0,
+            validated_at: None,
+            seat_info: None,
+            entry_count: 0,
+            is_inside: false,
+            last_entry_at: 0,
+            last_exit_at: 0,
+            last_checkin_nonce: 0,
+            bump: 255,
+        }
+    }
+
+    fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> TokenAccount {
+        use anchor_lang::solana_program::program_pack::Pack;
+        use anchor_spl::token::spl_token::state::{Account, AccountState};
+
+        let mut data = vec![0u8; Account::LEN];
+        let account = Account {
+            mint,
+            owner,
+            amount,
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        account.pack_into_slice(&mut data);
+        TokenAccount::try_deserialize_unchecked(&mut data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn reschedule_keeps_the_settlement_delay_and_never_settles_earlier() {
+        let mut event = event();
+        event.event_end_time = 1_000;
+        event.settles_at = 1_500;
+
+        assert_eq!(event.rescheduled_settlement(3_000), Some(3_500));
+        assert_eq!(event.rescheduled_settlement(800), Some(1_500));
+    }
+
+    #[test]
+    fn ticket_is_held_by_the_owner_of_its_token() {
+        let ticket = ticket();
+        let holder = Pubkey::new_unique();
+
+        assert!(ticket.is_held_by(&token_account(ticket.mint, holder, 1), &holder));
+        assert!(!ticket.is_held_by(&token_account(ticket.mint, holder, 1), &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn ticket_is_not_held_through_other_mints_or_empty_accounts() {
+        let ticket = ticket();
+        let holder = Pubkey::new_unique();
+
+        assert!(!ticket.is_held_by(&token_account(Pubkey::new_unique(), holder, 1), &holder));
+        assert!(!ticket.is_held_by(&token_account(ticket.mint, holder, 0), &holder));
+    }
+
+    fn listing(pricing: ListingPricing) -> MarketListing {
+        MarketListing {
+            seller: Pubkey::new_unique(),
+            ticket_mint: Pubkey::new_unique(),
+            event: Pubkey::new_unique(),
+            tier: Pubkey::new_unique(),
+            list_price: 1_000,
+            pricing,
+            original_price: 800,
+            created_at: 5_000,
+            expires_at: 10_000,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn fixed_listing_keeps_its_price() {
+        let listing = listing(ListingPricing::Fixed);
+        assert_eq!(listing.current_price(5_000), 1_000);
+        assert_eq!(listing.current_price(9_999), 1_000);
+    }
+
+    #[test]
+    fn dutch_listing_prices_from_its_listing_time() {
+        let listing = listing(ListingPricing::Dutch { min_price: 400, decay_bps: 1_000, decay_interval: 60 });
+
+        assert_eq!(listing.current_price(5_000), 1_000);
+        assert_eq!(listing.current_price(5_059), 1_000);
+        assert_eq!(listing.current_price(5_060), 900);
+        assert_eq!(listing.current_price(5_300), 500);
+        assert_eq!(listing.current_price(9_999), 400);
+    }
+
+    #[test]
+    fn dutch_listing_before_its_listing_time_is_at_start_price() {
+        let listing = listing(ListingPricing::Dutch { min_price: 400, decay_bps: 1_000, decay_interval: 60 });
+        assert_eq!(listing.current_price(0), 1_000);
+        assert_eq!(listing.current_price(i64::MIN), 1_000);
+    }
+}